@@ -1,29 +1,388 @@
 use std::env;
 use std::path::{Path, PathBuf};
 
+/// The environment variable pointing the build at a Rubber Band source tree other than the
+/// `rubberband-c` submodule.
+const SRC_DIR_ENV: &str = "RUBBERBAND_SRC_DIR";
+
+/// The oldest system library providing all of the C API used by the bindings. The live shifter
+/// functions were added in Rubber Band 4.0.
+#[cfg(feature = "system")]
+const MIN_SYSTEM_VERSION: &str = "4.0.0";
+
+/// Where the Rubber Band library comes from.
+enum Source {
+    /// A source tree containing `single/RubberBandSingle.cpp`, compiled along with the shim.
+    Vendored(PathBuf),
+    /// An installed library found with pkg-config, which only the shim is compiled against.
+    #[cfg(feature = "system")]
+    System(Box<pkg_config::Library>),
+}
+
+impl Source {
+    /// Use the source tree in `RUBBERBAND_SRC_DIR` if set, or else the system library with the
+    /// `system` feature, or else the `rubberband-c` submodule.
+    fn find() -> Self {
+        if let Some(dir) = env::var_os(SRC_DIR_ENV) {
+            return Self::vendored(PathBuf::from(dir));
+        }
+        #[cfg(feature = "system")]
+        return Self::system();
+        #[cfg(not(feature = "system"))]
+        Self::vendored(PathBuf::from("rubberband-c"))
+    }
+
+    fn vendored(dir: PathBuf) -> Self {
+        if !single_file(&dir).is_file() {
+            panic!(
+                "Rubber Band source not found: {} does not exist.\n\
+                 Run `git submodule update --init` to fetch the bundled source, set {} to a Rubber \
+                 Band source tree, or enable the `system` feature to link an installed librubberband.",
+                single_file(&dir).display(),
+                SRC_DIR_ENV
+            );
+        }
+        Source::Vendored(dir)
+    }
+
+    #[cfg(feature = "system")]
+    fn system() -> Self {
+        let library = probe("rubberband", MIN_SYSTEM_VERSION, "The `system` feature");
+        Source::System(Box::new(library))
+    }
+
+    /// The directories containing `rubberband/rubberband-c.h`.
+    fn include_dirs(&self) -> Vec<PathBuf> {
+        match self {
+            Source::Vendored(dir) => vec![dir.clone()],
+            #[cfg(feature = "system")]
+            Source::System(library) => library.include_paths.clone(),
+        }
+    }
+}
+
+/// The single-file build of the library in the source tree at `dir`.
+fn single_file(dir: &Path) -> PathBuf {
+    dir.join("single").join("RubberBandSingle.cpp")
+}
+
+/// The FFT implementation compiled into the single-file build of the bundled library, selected
+/// with the `fft-*` features.
+#[derive(Clone, Copy)]
+enum FftBackend {
+    /// The built-in FFT (`USE_BUILTIN_FFT`).
+    Builtin,
+    /// The bundled KissFFT (`HAVE_KISSFFT`).
+    KissFft,
+    /// An installed FFTW 3 (`HAVE_FFTW3`), found with pkg-config.
+    #[cfg(feature = "fft-fftw")]
+    Fftw,
+    /// Apple's vDSP (`HAVE_VDSP`), the default on Apple platforms.
+    Vdsp,
+}
+
+impl FftBackend {
+    /// Get the backend selected by the features, or `None` to use the library default.
+    fn from_features() -> Option<Self> {
+        let mut selected = Vec::new();
+        if cfg!(feature = "fft-builtin") {
+            selected.push(FftBackend::Builtin);
+        }
+        if cfg!(feature = "fft-kissfft") {
+            selected.push(FftBackend::KissFft);
+        }
+        #[cfg(feature = "fft-fftw")]
+        selected.push(FftBackend::Fftw);
+
+        at_most_one("FFT", "fft", selected, Self::name)
+    }
+
+    /// The FFT the single-file build defaults to, used if none is selected.
+    fn library_default() -> Self {
+        if cfg!(target_os = "macos") || cfg!(target_os = "ios") {
+            FftBackend::Vdsp
+        } else {
+            FftBackend::Builtin
+        }
+    }
+
+    /// The name reported by `FFT_BACKEND`, which is also the feature name without `fft-`.
+    fn name(self) -> &'static str {
+        match self {
+            FftBackend::Builtin => "builtin",
+            FftBackend::KissFft => "kissfft",
+            #[cfg(feature = "fft-fftw")]
+            FftBackend::Fftw => "fftw",
+            FftBackend::Vdsp => "vdsp",
+        }
+    }
+
+    /// Add the preprocessor defines selecting this backend, which must be the only FFT defined.
+    fn define(self, build: &mut cc::Build) {
+        match self {
+            FftBackend::Builtin => {
+                build.define("USE_BUILTIN_FFT", None);
+            }
+            FftBackend::KissFft => {
+                build.define("HAVE_KISSFFT", None).define("kiss_fft_scalar", "double");
+            }
+            #[cfg(feature = "fft-fftw")]
+            FftBackend::Fftw => {
+                // Rubber Band only uses the double-precision FFTW library
+                build.define("HAVE_FFTW3", None).define("FFTW_DOUBLE_ONLY", None);
+            }
+            FftBackend::Vdsp => {
+                build.define("HAVE_VDSP", None);
+            }
+        }
+    }
+}
+
+/// The resampler compiled into the single-file build of the bundled library, selected with the
+/// `resampler-*` features.
+#[derive(Clone, Copy)]
+enum ResamplerBackend {
+    /// The built-in resampler (`USE_BQRESAMPLER`), the default.
+    Builtin,
+    /// An installed libsamplerate (`HAVE_LIBSAMPLERATE`), found with pkg-config.
+    #[cfg(feature = "resampler-libsamplerate")]
+    Libsamplerate,
+    /// The bundled Speex resampler (`USE_SPEEX`).
+    Speex,
+}
+
+impl ResamplerBackend {
+    /// Get the backend selected by the features, or `None` to use the library default.
+    fn from_features() -> Option<Self> {
+        let mut selected = Vec::new();
+        if cfg!(feature = "resampler-builtin") {
+            selected.push(ResamplerBackend::Builtin);
+        }
+        #[cfg(feature = "resampler-libsamplerate")]
+        selected.push(ResamplerBackend::Libsamplerate);
+        if cfg!(feature = "resampler-speex") {
+            selected.push(ResamplerBackend::Speex);
+        }
+
+        at_most_one("resampler", "resampler", selected, Self::name)
+    }
+
+    /// The name reported by `RESAMPLER_BACKEND`, which is also the feature name without
+    /// `resampler-`.
+    fn name(self) -> &'static str {
+        match self {
+            ResamplerBackend::Builtin => "builtin",
+            #[cfg(feature = "resampler-libsamplerate")]
+            ResamplerBackend::Libsamplerate => "libsamplerate",
+            ResamplerBackend::Speex => "speex",
+        }
+    }
+
+    /// Add the preprocessor define selecting this backend, which must be the only resampler
+    /// defined.
+    fn define(self, build: &mut cc::Build) {
+        let define = match self {
+            ResamplerBackend::Builtin => "USE_BQRESAMPLER",
+            #[cfg(feature = "resampler-libsamplerate")]
+            ResamplerBackend::Libsamplerate => "HAVE_LIBSAMPLERATE",
+            ResamplerBackend::Speex => "USE_SPEEX",
+        };
+        build.define(define, None);
+    }
+}
+
+/// Add the defines configuring the single-file build, with the given FFT and resampler, and
+/// threading unless the `no-threading` feature is enabled.
+///
+/// `single/RubberBandSingle.cpp` only uses the configuration passed to the compiler if
+/// `ALREADY_CONFIGURED` is defined. Otherwise it defines its own on top of it: `USE_BQRESAMPLER`,
+/// `NO_TIMING`, `NO_THREADING`, `NO_THREAD_CHECKS`, and `HAVE_VDSP` on Apple platforms or
+/// `USE_BUILTIN_FFT` elsewhere. Then a selected FFT or resampler would be compiled in alongside
+/// the default one, and threading could never be enabled. So the whole configuration is defined
+/// here.
+fn configure(build: &mut cc::Build, fft: FftBackend, resampler: ResamplerBackend) {
+    build.define("ALREADY_CONFIGURED", None);
+    fft.define(build);
+    resampler.define(build);
+    // Timing is only used for debug output
+    build.define("NO_TIMING", None);
+
+    if cfg!(feature = "no-threading") {
+        build.define("NO_THREADING", None).define("NO_THREAD_CHECKS", None);
+        // Reported by `NO_THREADING`
+        println!("cargo:rustc-cfg=rubberband_no_threading");
+    } else if has_pthreads() {
+        build.define("USE_PTHREADS", "1");
+    }
+}
+
+/// Whether the worker threads of the library are pthreads, as on all targets except Windows.
+fn has_pthreads() -> bool {
+    env::var("CARGO_CFG_TARGET_FAMILY").is_ok_and(|family| family.split(',').any(|f| f == "unix"))
+}
+
+/// Check that at most one of the backends of a kind was selected, with features named
+/// `<prefix>-<name>`.
+fn at_most_one<T: Copy>(kind: &str, prefix: &str, selected: Vec<T>, name: fn(T) -> &'static str) -> Option<T> {
+    if selected.len() > 1 {
+        let names: Vec<_> = selected.iter().map(|&backend| format!("`{}-{}`", prefix, name(backend))).collect();
+        panic!("The {} features are mutually exclusive, but {} are enabled", kind, names.join(" and "));
+    }
+    selected.first().copied()
+}
+
+/// Find a library with pkg-config, without linking it yet: static libraries must be linked after
+/// the libraries depending on them, so call [link()] once those have been compiled.
+#[cfg(any(feature = "system", feature = "fft-fftw", feature = "resampler-libsamplerate"))]
+fn probe(name: &str, min_version: &str, purpose: &str) -> pkg_config::Library {
+    pkg_config::Config::new()
+        .atleast_version(min_version)
+        .cargo_metadata(false)
+        .probe(name)
+        .unwrap_or_else(|err| {
+            panic!(
+                "{} requires {} {} or newer, found with pkg-config: {}",
+                purpose, name, min_version, err
+            )
+        })
+}
+
+/// Link a library found by [probe()].
+#[cfg(any(feature = "system", feature = "fft-fftw", feature = "resampler-libsamplerate"))]
+fn link(library: &pkg_config::Library) {
+    for path in &library.link_paths {
+        println!("cargo:rustc-link-search=native={}", path.display());
+    }
+    for lib in &library.libs {
+        println!("cargo:rustc-link-lib={}", lib);
+    }
+    for path in &library.framework_paths {
+        println!("cargo:rustc-link-search=framework={}", path.display());
+    }
+    for framework in &library.frameworks {
+        println!("cargo:rustc-link-lib=framework={}", framework);
+    }
+}
+
 fn main() {
-    // Path to the Rubberband source directory
-    let rubberband_src = Path::new("rubberband-c");
+    println!("cargo:rerun-if-env-changed={}", SRC_DIR_ENV);
+    println!("cargo:rustc-check-cfg=cfg(rubberband_no_threading)");
+    let source = Source::find();
+    let include_dirs = source.include_dirs();
 
-    // Build the single-file version
     let mut build = cc::Build::new();
     build.cpp(true)
-        .file(format!("{}/single/RubberBandSingle.cpp", rubberband_src.display()))
+        // Exception-safe wrappers around the C API
+        .file("src/shim.cpp")
+        .includes(&include_dirs)
         .flag_if_supported("-std=c++11")
         .flag_if_supported("-Wno-unused-parameter");
 
-    // On Apple platforms, the single file build would use vDSP for FFT by default.
-    // Therefore, we need to link the Accelerate framework.
-    if cfg!(target_os = "macos") || cfg!(target_os = "ios") {
-        println!("cargo:rustc-link-lib=framework=Accelerate");
+    let fft = FftBackend::from_features();
+    let resampler = ResamplerBackend::from_features();
+    match &source {
+        Source::Vendored(dir) => {
+            let fft = fft.unwrap_or_else(FftBackend::library_default);
+            let resampler = resampler.unwrap_or(ResamplerBackend::Builtin);
+
+            // Build the single-file version
+            build.file(single_file(dir));
+            println!("cargo:rerun-if-changed={}", single_file(dir).display());
+            configure(&mut build, fft, resampler);
+            #[cfg(feature = "fft-fftw")]
+            let fftw = probe("fftw3", "3.0.0", "The `fft-fftw` feature");
+            #[cfg(feature = "fft-fftw")]
+            build.includes(&fftw.include_paths);
+            #[cfg(feature = "resampler-libsamplerate")]
+            let libsamplerate = probe("samplerate", "0.1.8", "The `resampler-libsamplerate` feature");
+            #[cfg(feature = "resampler-libsamplerate")]
+            build.includes(&libsamplerate.include_paths);
+
+            // vDSP is part of the Accelerate framework
+            if let FftBackend::Vdsp = fft {
+                println!("cargo:rustc-link-lib=framework=Accelerate");
+            }
+
+            // Compile the library
+            build.compile("rubberband");
+
+            // Then the FFT and resampler libraries it depends on
+            match fft {
+                FftBackend::KissFft => {
+                    // KissFFT is C, so it is compiled separately from the C++ sources
+                    let kissfft = dir.join("src").join("ext").join("kissfft");
+                    cc::Build::new()
+                        .file(kissfft.join("kiss_fft.c"))
+                        .file(kissfft.join("kiss_fftr.c"))
+                        .include(&kissfft)
+                        .define("kiss_fft_scalar", "double")
+                        .compile("rubberband_kissfft");
+                }
+                #[cfg(feature = "fft-fftw")]
+                FftBackend::Fftw => link(&fftw),
+                FftBackend::Builtin | FftBackend::Vdsp => {}
+            }
+            match resampler {
+                ResamplerBackend::Speex => {
+                    // Like KissFFT, the Speex resampler is C
+                    let speex = dir.join("src").join("ext").join("speex");
+                    cc::Build::new()
+                        .file(speex.join("resample.c"))
+                        .include(&speex)
+                        .compile("rubberband_speex");
+                }
+                #[cfg(feature = "resampler-libsamplerate")]
+                ResamplerBackend::Libsamplerate => link(&libsamplerate),
+                ResamplerBackend::Builtin => {}
+            }
+            // And pthread, which Android has in its libc
+            if !cfg!(feature = "no-threading")
+                && has_pthreads()
+                && env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("android")
+            {
+                println!("cargo:rustc-link-lib=pthread");
+            }
+            println!("cargo:rustc-env=RUBBERBAND_FFT_BACKEND={}", fft.name());
+            println!("cargo:rustc-env=RUBBERBAND_RESAMPLER_BACKEND={}", resampler.name());
+        }
+        #[cfg(feature = "system")]
+        Source::System(library) => {
+            // The installed library was built with its own FFT, resampler and threading support
+            if fft.is_some() || resampler.is_some() || cfg!(feature = "no-threading") {
+                panic!(
+                    "The FFT, resampler and `no-threading` features only apply to the bundled source, and \
+                     cannot be used with the `system` feature"
+                );
+            }
+
+            // A different name, so that the system library is not shadowed by the shim
+            build.compile("rubberband_shim");
+            link(library);
+            println!("cargo:rustc-env=RUBBERBAND_FFT_BACKEND=system");
+            println!("cargo:rustc-env=RUBBERBAND_RESAMPLER_BACKEND=system");
+        }
     }
 
-    // Compile the library
-    build.compile("rubberband");
+    // The checked-in src/bindings.rs is used unless the `bindgen` feature is enabled
+    #[cfg(feature = "bindgen")]
+    generate_bindings(&include_dirs);
+
+    // Tell cargo to invalidate the built crate whenever the source files change
+    println!("cargo:rerun-if-changed=src/shim.cpp");
+    println!("cargo:rerun-if-changed=src/shim.h");
+}
 
-    // Generate bindings
+/// Generate the bindings into `$OUT_DIR/bindings.rs`. These are the same as running the
+/// `bindgen` command line tool on `src/shim.h`, which is how the checked-in `src/bindings.rs` is
+/// updated.
+#[cfg(feature = "bindgen")]
+fn generate_bindings(include_dirs: &[PathBuf]) {
     let bindings = bindgen::Builder::default()
-        .header(format!("{}/rubberband/rubberband-c.h", rubberband_src.display()))
+        // The shim header includes the C API header
+        .header("src/shim.h")
+        .clang_args(include_dirs.iter().map(|dir| format!("-I{}", dir.display())))
+        // The version macros depend on the library found, so they are left out of the bindings
+        .blocklist_item("RUBBERBAND_.*VERSION")
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -35,7 +394,4 @@ fn main() {
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
-
-    // Tell cargo to invalidate the built crate whenever the source files change
-    println!("cargo:rerun-if-changed={}/single/RubberBandSingle.cpp", rubberband_src.display());
 }
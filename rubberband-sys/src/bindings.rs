@@ -0,0 +1,382 @@
+/* automatically generated by rust-bindgen 0.71.1 */
+
+pub const RubberBandOption_RubberBandOptionProcessOffline: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionProcessRealTime: RubberBandOption = 1;
+pub const RubberBandOption_RubberBandOptionStretchElastic: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionStretchPrecise: RubberBandOption = 16;
+pub const RubberBandOption_RubberBandOptionTransientsCrisp: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionTransientsMixed: RubberBandOption = 256;
+pub const RubberBandOption_RubberBandOptionTransientsSmooth: RubberBandOption = 512;
+pub const RubberBandOption_RubberBandOptionDetectorCompound: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionDetectorPercussive: RubberBandOption = 1024;
+pub const RubberBandOption_RubberBandOptionDetectorSoft: RubberBandOption = 2048;
+pub const RubberBandOption_RubberBandOptionPhaseLaminar: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionPhaseIndependent: RubberBandOption = 8192;
+pub const RubberBandOption_RubberBandOptionThreadingAuto: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionThreadingNever: RubberBandOption = 65536;
+pub const RubberBandOption_RubberBandOptionThreadingAlways: RubberBandOption = 131072;
+pub const RubberBandOption_RubberBandOptionWindowStandard: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionWindowShort: RubberBandOption = 1048576;
+pub const RubberBandOption_RubberBandOptionWindowLong: RubberBandOption = 2097152;
+pub const RubberBandOption_RubberBandOptionSmoothingOff: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionSmoothingOn: RubberBandOption = 8388608;
+pub const RubberBandOption_RubberBandOptionFormantShifted: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionFormantPreserved: RubberBandOption = 16777216;
+pub const RubberBandOption_RubberBandOptionPitchHighSpeed: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionPitchHighQuality: RubberBandOption = 33554432;
+pub const RubberBandOption_RubberBandOptionPitchHighConsistency: RubberBandOption = 67108864;
+pub const RubberBandOption_RubberBandOptionChannelsApart: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionChannelsTogether: RubberBandOption = 268435456;
+pub const RubberBandOption_RubberBandOptionEngineFaster: RubberBandOption = 0;
+pub const RubberBandOption_RubberBandOptionEngineFiner: RubberBandOption = 536870912;
+pub type RubberBandOption = ::std::os::raw::c_uint;
+pub type RubberBandOptions = ::std::os::raw::c_int;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct RubberBandState_ {
+    _unused: [u8; 0],
+}
+pub type RubberBandState = *mut RubberBandState_;
+unsafe extern "C" {
+    pub fn rubberband_new(
+        sampleRate: ::std::os::raw::c_uint,
+        channels: ::std::os::raw::c_uint,
+        options: RubberBandOptions,
+        initialTimeRatio: f64,
+        initialPitchScale: f64,
+    ) -> RubberBandState;
+}
+unsafe extern "C" {
+    pub fn rubberband_delete(arg1: RubberBandState);
+}
+unsafe extern "C" {
+    pub fn rubberband_reset(arg1: RubberBandState);
+}
+unsafe extern "C" {
+    pub fn rubberband_get_engine_version(arg1: RubberBandState) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn rubberband_set_time_ratio(arg1: RubberBandState, ratio: f64);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_pitch_scale(arg1: RubberBandState, scale: f64);
+}
+unsafe extern "C" {
+    pub fn rubberband_get_time_ratio(arg1: RubberBandState) -> f64;
+}
+unsafe extern "C" {
+    pub fn rubberband_get_pitch_scale(arg1: RubberBandState) -> f64;
+}
+unsafe extern "C" {
+    pub fn rubberband_set_formant_scale(arg1: RubberBandState, scale: f64);
+}
+unsafe extern "C" {
+    pub fn rubberband_get_formant_scale(arg1: RubberBandState) -> f64;
+}
+unsafe extern "C" {
+    pub fn rubberband_get_preferred_start_pad(arg1: RubberBandState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_get_start_delay(arg1: RubberBandState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_get_latency(arg1: RubberBandState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_set_transients_option(arg1: RubberBandState, options: RubberBandOptions);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_detector_option(arg1: RubberBandState, options: RubberBandOptions);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_phase_option(arg1: RubberBandState, options: RubberBandOptions);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_formant_option(arg1: RubberBandState, options: RubberBandOptions);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_pitch_option(arg1: RubberBandState, options: RubberBandOptions);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_expected_input_duration(
+        arg1: RubberBandState,
+        samples: ::std::os::raw::c_uint,
+    );
+}
+unsafe extern "C" {
+    pub fn rubberband_get_samples_required(arg1: RubberBandState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_set_max_process_size(arg1: RubberBandState, samples: ::std::os::raw::c_uint);
+}
+unsafe extern "C" {
+    pub fn rubberband_get_process_size_limit(arg1: RubberBandState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_set_key_frame_map(
+        arg1: RubberBandState,
+        keyframecount: ::std::os::raw::c_uint,
+        from: *mut ::std::os::raw::c_uint,
+        to: *mut ::std::os::raw::c_uint,
+    );
+}
+unsafe extern "C" {
+    pub fn rubberband_study(
+        arg1: RubberBandState,
+        input: *const *const f32,
+        samples: ::std::os::raw::c_uint,
+        final_: ::std::os::raw::c_int,
+    );
+}
+unsafe extern "C" {
+    pub fn rubberband_process(
+        arg1: RubberBandState,
+        input: *const *const f32,
+        samples: ::std::os::raw::c_uint,
+        final_: ::std::os::raw::c_int,
+    );
+}
+unsafe extern "C" {
+    pub fn rubberband_available(arg1: RubberBandState) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn rubberband_retrieve(
+        arg1: RubberBandState,
+        output: *const *mut f32,
+        samples: ::std::os::raw::c_uint,
+    ) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_get_channel_count(arg1: RubberBandState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_calculate_stretch(arg1: RubberBandState);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_debug_level(arg1: RubberBandState, level: ::std::os::raw::c_int);
+}
+unsafe extern "C" {
+    pub fn rubberband_set_default_debug_level(level: ::std::os::raw::c_int);
+}
+pub const RubberBandLiveOption_RubberBandLiveOptionWindowShort: RubberBandLiveOption = 0;
+pub const RubberBandLiveOption_RubberBandLiveOptionWindowMedium: RubberBandLiveOption = 1048576;
+pub const RubberBandLiveOption_RubberBandLiveOptionFormantShifted: RubberBandLiveOption = 0;
+pub const RubberBandLiveOption_RubberBandLiveOptionFormantPreserved: RubberBandLiveOption = 16777216;
+pub const RubberBandLiveOption_RubberBandLiveOptionChannelsApart: RubberBandLiveOption = 0;
+pub const RubberBandLiveOption_RubberBandLiveOptionChannelsTogether: RubberBandLiveOption = 268435456;
+pub type RubberBandLiveOption = ::std::os::raw::c_uint;
+pub type RubberBandLiveOptions = ::std::os::raw::c_int;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct RubberBandLiveState_ {
+    _unused: [u8; 0],
+}
+pub type RubberBandLiveState = *mut RubberBandLiveState_;
+unsafe extern "C" {
+    pub fn rubberband_live_new(
+        sampleRate: ::std::os::raw::c_uint,
+        channels: ::std::os::raw::c_uint,
+        options: RubberBandLiveOptions,
+    ) -> RubberBandLiveState;
+}
+unsafe extern "C" {
+    pub fn rubberband_live_delete(arg1: RubberBandLiveState);
+}
+unsafe extern "C" {
+    pub fn rubberband_live_reset(arg1: RubberBandLiveState);
+}
+unsafe extern "C" {
+    pub fn rubberband_live_set_pitch_scale(arg1: RubberBandLiveState, scale: f64);
+}
+unsafe extern "C" {
+    pub fn rubberband_live_get_pitch_scale(arg1: RubberBandLiveState) -> f64;
+}
+unsafe extern "C" {
+    pub fn rubberband_live_set_formant_scale(arg1: RubberBandLiveState, scale: f64);
+}
+unsafe extern "C" {
+    pub fn rubberband_live_get_formant_scale(arg1: RubberBandLiveState) -> f64;
+}
+unsafe extern "C" {
+    pub fn rubberband_live_get_start_delay(arg1: RubberBandLiveState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_live_set_formant_option(
+        arg1: RubberBandLiveState,
+        options: RubberBandLiveOptions,
+    );
+}
+unsafe extern "C" {
+    pub fn rubberband_live_get_block_size(arg1: RubberBandLiveState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_live_shift(
+        arg1: RubberBandLiveState,
+        input: *const *const f32,
+        output: *const *mut f32,
+    );
+}
+unsafe extern "C" {
+    pub fn rubberband_live_get_channel_count(arg1: RubberBandLiveState) -> ::std::os::raw::c_uint;
+}
+unsafe extern "C" {
+    pub fn rubberband_live_set_debug_level(arg1: RubberBandLiveState, level: ::std::os::raw::c_int);
+}
+unsafe extern "C" {
+    pub fn rubberband_live_set_default_debug_level(level: ::std::os::raw::c_int);
+}
+pub const RubberBandShimStatus_RubberBandShimOk: RubberBandShimStatus = 0;
+pub const RubberBandShimStatus_RubberBandShimOutOfMemory: RubberBandShimStatus = 1;
+pub const RubberBandShimStatus_RubberBandShimInvalidArgument: RubberBandShimStatus = 2;
+pub const RubberBandShimStatus_RubberBandShimException: RubberBandShimStatus = 3;
+pub type RubberBandShimStatus = ::std::os::raw::c_uint;
+unsafe extern "C" {
+    pub fn rubberband_shim_new(
+        sampleRate: ::std::os::raw::c_uint,
+        channels: ::std::os::raw::c_uint,
+        options: RubberBandOptions,
+        initialTimeRatio: f64,
+        initialPitchScale: f64,
+        state: *mut RubberBandState,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_reset(state: RubberBandState) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_time_ratio(
+        state: RubberBandState,
+        ratio: f64,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_pitch_scale(
+        state: RubberBandState,
+        scale: f64,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_formant_scale(
+        state: RubberBandState,
+        scale: f64,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_transients_option(
+        state: RubberBandState,
+        options: RubberBandOptions,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_detector_option(
+        state: RubberBandState,
+        options: RubberBandOptions,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_phase_option(
+        state: RubberBandState,
+        options: RubberBandOptions,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_formant_option(
+        state: RubberBandState,
+        options: RubberBandOptions,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_pitch_option(
+        state: RubberBandState,
+        options: RubberBandOptions,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_expected_input_duration(
+        state: RubberBandState,
+        samples: ::std::os::raw::c_uint,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_max_process_size(
+        state: RubberBandState,
+        samples: ::std::os::raw::c_uint,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_set_key_frame_map(
+        state: RubberBandState,
+        keyframecount: ::std::os::raw::c_uint,
+        from: *mut ::std::os::raw::c_uint,
+        to: *mut ::std::os::raw::c_uint,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_study(
+        state: RubberBandState,
+        input: *const *const f32,
+        samples: ::std::os::raw::c_uint,
+        final_: ::std::os::raw::c_int,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_process(
+        state: RubberBandState,
+        input: *const *const f32,
+        samples: ::std::os::raw::c_uint,
+        final_: ::std::os::raw::c_int,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_retrieve(
+        state: RubberBandState,
+        output: *const *mut f32,
+        samples: ::std::os::raw::c_uint,
+        retrieved: *mut ::std::os::raw::c_uint,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_available(
+        state: RubberBandState,
+        available: *mut ::std::os::raw::c_int,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_calculate_stretch(state: RubberBandState) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_live_new(
+        sampleRate: ::std::os::raw::c_uint,
+        channels: ::std::os::raw::c_uint,
+        options: RubberBandLiveOptions,
+        state: *mut RubberBandLiveState,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_live_reset(state: RubberBandLiveState) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_live_set_pitch_scale(
+        state: RubberBandLiveState,
+        scale: f64,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_live_set_formant_scale(
+        state: RubberBandLiveState,
+        scale: f64,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_live_set_formant_option(
+        state: RubberBandLiveState,
+        options: RubberBandLiveOptions,
+    ) -> RubberBandShimStatus;
+}
+unsafe extern "C" {
+    pub fn rubberband_shim_live_shift(
+        state: RubberBandLiveState,
+        input: *const *const f32,
+        output: *const *mut f32,
+    ) -> RubberBandShimStatus;
+}
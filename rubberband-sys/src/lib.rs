@@ -1,7 +1,35 @@
 #![allow(non_upper_case_globals)]
 
+// The checked-in bindings, generated from src/shim.h and the vendored rubberband-c.h. With the
+// `bindgen` feature, they are generated at build time instead.
+#[cfg(not(feature = "bindgen"))]
+include!("bindings.rs");
+#[cfg(feature = "bindgen")]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+/// The FFT implementation compiled into the Rubber Band library.
+///
+/// This is `"builtin"`, `"kissfft"` or `"fftw"` when selected with the `fft-builtin`,
+/// `fft-kissfft` or `fft-fftw` feature. Otherwise it is the library default: `"vdsp"` on Apple
+/// platforms and `"builtin"` elsewhere. With the `system` feature, the FFT of the installed
+/// library is not known, and this is `"system"`.
+pub const FFT_BACKEND: &str = env!("RUBBERBAND_FFT_BACKEND");
+
+/// The resampler compiled into the Rubber Band library, used for pitch shifting.
+///
+/// This is `"builtin"`, `"libsamplerate"` or `"speex"` when selected with the
+/// `resampler-builtin`, `resampler-libsamplerate` or `resampler-speex` feature, and `"builtin"`
+/// otherwise. With the `system` feature, this is `"system"`, like [FFT_BACKEND].
+pub const RESAMPLER_BACKEND: &str = env!("RUBBERBAND_RESAMPLER_BACKEND");
+
+/// Whether the bundled library was compiled without threading (`NO_THREADING`), in which case it
+/// never starts worker threads, whatever the threading option.
+///
+/// This is `true` with the `no-threading` feature, and `false` otherwise: the bundled source is
+/// then compiled with threading. With the `system` feature, this is `false`, as the threading
+/// support of an installed library is not known.
+pub const NO_THREADING: bool = cfg!(rubberband_no_threading);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,40 +151,35 @@ mod tests {
                 // Process the audio block by block
                 for n in (0..num_samples).step_by(block_size) {
                     // Fill input with a simple sine wave
-                    for i in 0..block_size {
-                        input_buffers[0][i] = ((n + i) as f32 * 0.1).sin(); // 0 dBFS
+                    for (i, sample) in input_buffers[0].iter_mut().enumerate() {
+                        *sample = ((n + i) as f32 * 0.1).sin(); // 0 dBFS
                     }
 
                     // Process the audio
-                    let input_view = vec![input_buffers[0].as_ptr()];
-                    let mut output_view = vec![output_buffers[0].as_mut_ptr()];
+                    let input_view = [input_buffers[0].as_ptr()];
+                    let mut output_view = [output_buffers[0].as_mut_ptr()];
                     rubberband_live_shift(state, input_view.as_ptr(), output_view.as_mut_ptr());
 
                     // Save the output samples
-                    for i in 0..block_size {
-                        output_samples[n + i] = output_buffers[0][i];
-                    }
+                    output_samples[n..n + block_size].copy_from_slice(&output_buffers[0]);
                 }
 
                 // The first start_delay output samples should be zeros
-                for i in 0..start_delay {
+                for (i, sample) in output_samples[..start_delay].iter().enumerate() {
                     assert!(
-                        output_samples[i].abs() < 1e-3,
+                        sample.abs() < 1e-3,
                         concat!(
                             "Output sample should be smaller than -60 dBFS before the start delay ({}),",
                             "got {} at sample {}"
                         ),
                         start_delay,
-                        i,
-                        output_samples[i]
+                        sample,
+                        i
                     );
                 }
 
                 // The output samples should not be all zeros (basic sanity check)
-                let mut sum = 0.0;
-                for i in 0..num_samples {
-                    sum += output_samples[i].abs();
-                }
+                let sum: f32 = output_samples.iter().map(|sample| sample.abs()).sum();
                 assert!(sum > 0.0, "Output should contain non-zero samples");
 
                 // Clean up
@@ -191,19 +214,17 @@ mod tests {
                 // Process the audio block by block
                 for n in (0..num_samples).step_by(block_size) {
                     // Fill input with a sine wave
-                    for i in 0..block_size {
-                        input_buffers[0][i] = (2.0 * PI * (n + i) as f32 / period).sin(); // 0 dBFS
+                    for (i, sample) in input_buffers[0].iter_mut().enumerate() {
+                        *sample = (2.0 * PI * (n + i) as f32 / period).sin(); // 0 dBFS
                     }
 
                     // Process the audio
-                    let input_view = vec![input_buffers[0].as_ptr()];
-                    let mut output_view = vec![output_buffers[0].as_mut_ptr()];
+                    let input_view = [input_buffers[0].as_ptr()];
+                    let mut output_view = [output_buffers[0].as_mut_ptr()];
                     rubberband_live_shift(state, input_view.as_ptr(), output_view.as_mut_ptr());
 
                     // Save the output samples
-                    for i in 0..block_size {
-                        output_samples[n + i] = output_buffers[0][i];
-                    }
+                    output_samples[n..n + block_size].copy_from_slice(&output_buffers[0]);
                 }
 
                 // Check the frequency by measuring zero-crossings in the last 0.5 seconds
@@ -212,11 +233,11 @@ mod tests {
                 let mut last_sample = output_samples[last_half_second];
 
                 // Collect all zero-crossing indices
-                for i in (last_half_second + 1)..num_samples {
-                    if output_samples[i] * last_sample < 0.0 {
+                for (i, &sample) in output_samples.iter().enumerate().skip(last_half_second + 1) {
+                    if sample * last_sample < 0.0 {
                         zero_crossing_indices.push(i);
                     }
-                    last_sample = output_samples[i];
+                    last_sample = sample;
                 }
 
                 // Calculate the mean zero-crossing period (half-period)
@@ -245,4 +266,111 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_fft_backend() {
+        let expected = if cfg!(feature = "fft-builtin") {
+            "builtin"
+        } else if cfg!(feature = "fft-kissfft") {
+            "kissfft"
+        } else if cfg!(feature = "fft-fftw") {
+            "fftw"
+        } else if cfg!(target_os = "macos") || cfg!(target_os = "ios") {
+            "vdsp"
+        } else {
+            "builtin"
+        };
+        // A source tree given with RUBBERBAND_SRC_DIR is still built with the `system` feature
+        assert!(
+            FFT_BACKEND == expected || (cfg!(feature = "system") && FFT_BACKEND == "system"),
+            "Unexpected FFT backend: {}",
+            FFT_BACKEND
+        );
+    }
+
+    #[test]
+    fn test_resampler_backend() {
+        let expected = if cfg!(feature = "resampler-libsamplerate") {
+            "libsamplerate"
+        } else if cfg!(feature = "resampler-speex") {
+            "speex"
+        } else {
+            "builtin"
+        };
+        assert!(
+            RESAMPLER_BACKEND == expected || (cfg!(feature = "system") && RESAMPLER_BACKEND == "system"),
+            "Unexpected resampler backend: {}",
+            RESAMPLER_BACKEND
+        );
+    }
+
+    /// The bindings generated at build time match the checked-in ones, byte for byte. They are
+    /// generated from the bundled headers, unless an installed library is used.
+    #[cfg(all(feature = "bindgen", not(feature = "system")))]
+    #[test]
+    fn test_bindings_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+        assert!(
+            generated == include_str!("bindings.rs"),
+            "src/bindings.rs is out of date: regenerate it with bindgen as described in the README"
+        );
+    }
+
+    mod shim {
+        use super::*;
+
+        #[test]
+        fn test_shim_live() {
+            unsafe {
+                let mut state: RubberBandLiveState = std::ptr::null_mut();
+                let status = rubberband_shim_live_new(44100, 1, 0, &mut state);
+                assert_eq!(status, RubberBandShimStatus_RubberBandShimOk);
+                assert!(!state.is_null());
+
+                assert_eq!(rubberband_shim_live_set_pitch_scale(state, 1.5), RubberBandShimStatus_RubberBandShimOk);
+                assert_eq!(rubberband_live_get_pitch_scale(state), 1.5);
+
+                // Process one block through the shim
+                let block_size = rubberband_live_get_block_size(state) as usize;
+                let input = vec![0.5f32; block_size];
+                let mut output = vec![0.0f32; block_size];
+                let input_view = [input.as_ptr()];
+                let output_view = [output.as_mut_ptr()];
+                let status = rubberband_shim_live_shift(state, input_view.as_ptr(), output_view.as_ptr());
+                assert_eq!(status, RubberBandShimStatus_RubberBandShimOk);
+
+                assert_eq!(rubberband_shim_live_reset(state), RubberBandShimStatus_RubberBandShimOk);
+                rubberband_live_delete(state);
+            }
+        }
+
+        #[test]
+        fn test_shim_stretcher() {
+            unsafe {
+                let mut state: RubberBandState = std::ptr::null_mut();
+                let status = rubberband_shim_new(44100, 1, 0, 1.0, 1.0, &mut state);
+                assert_eq!(status, RubberBandShimStatus_RubberBandShimOk);
+                assert!(!state.is_null());
+
+                let input = vec![0.5f32; 4096];
+                let input_view = [input.as_ptr()];
+                assert_eq!(rubberband_shim_study(state, input_view.as_ptr(), 4096, 1), RubberBandShimStatus_RubberBandShimOk);
+                assert_eq!(rubberband_shim_process(state, input_view.as_ptr(), 4096, 1), RubberBandShimStatus_RubberBandShimOk);
+
+                let mut available = -1;
+                assert_eq!(rubberband_shim_available(state, &mut available), RubberBandShimStatus_RubberBandShimOk);
+                assert!(available >= 0);
+
+                // The number of retrieved samples is passed back through the last argument
+                let mut output = vec![0.0f32; 8192];
+                let output_view = [output.as_mut_ptr()];
+                let mut retrieved = 0;
+                let status = rubberband_shim_retrieve(state, output_view.as_ptr(), 8192, &mut retrieved);
+                assert_eq!(status, RubberBandShimStatus_RubberBandShimOk);
+                assert!(retrieved <= 8192);
+
+                rubberband_delete(state);
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,177 @@
+#include "shim.h"
+
+#include <new>
+#include <stdexcept>
+
+namespace {
+
+// Run `call`, translating any exception it throws into a status code.
+template <typename F>
+RubberBandShimStatus guarded(F call)
+{
+    try {
+        call();
+        return RubberBandShimOk;
+    } catch (const std::bad_alloc &) {
+        return RubberBandShimOutOfMemory;
+    } catch (const std::logic_error &) {
+        return RubberBandShimInvalidArgument;
+    } catch (...) {
+        return RubberBandShimException;
+    }
+}
+
+}
+
+extern "C" {
+
+RubberBandShimStatus rubberband_shim_new(unsigned int sampleRate,
+                                         unsigned int channels,
+                                         RubberBandOptions options,
+                                         double initialTimeRatio,
+                                         double initialPitchScale,
+                                         RubberBandState *state)
+{
+    *state = nullptr;
+    return guarded([&] {
+        *state = rubberband_new(sampleRate, channels, options, initialTimeRatio, initialPitchScale);
+    });
+}
+
+RubberBandShimStatus rubberband_shim_reset(RubberBandState state)
+{
+    return guarded([&] { rubberband_reset(state); });
+}
+
+RubberBandShimStatus rubberband_shim_set_time_ratio(RubberBandState state, double ratio)
+{
+    return guarded([&] { rubberband_set_time_ratio(state, ratio); });
+}
+
+RubberBandShimStatus rubberband_shim_set_pitch_scale(RubberBandState state, double scale)
+{
+    return guarded([&] { rubberband_set_pitch_scale(state, scale); });
+}
+
+RubberBandShimStatus rubberband_shim_set_formant_scale(RubberBandState state, double scale)
+{
+    return guarded([&] { rubberband_set_formant_scale(state, scale); });
+}
+
+RubberBandShimStatus rubberband_shim_set_transients_option(RubberBandState state, RubberBandOptions options)
+{
+    return guarded([&] { rubberband_set_transients_option(state, options); });
+}
+
+RubberBandShimStatus rubberband_shim_set_detector_option(RubberBandState state, RubberBandOptions options)
+{
+    return guarded([&] { rubberband_set_detector_option(state, options); });
+}
+
+RubberBandShimStatus rubberband_shim_set_phase_option(RubberBandState state, RubberBandOptions options)
+{
+    return guarded([&] { rubberband_set_phase_option(state, options); });
+}
+
+RubberBandShimStatus rubberband_shim_set_formant_option(RubberBandState state, RubberBandOptions options)
+{
+    return guarded([&] { rubberband_set_formant_option(state, options); });
+}
+
+RubberBandShimStatus rubberband_shim_set_pitch_option(RubberBandState state, RubberBandOptions options)
+{
+    return guarded([&] { rubberband_set_pitch_option(state, options); });
+}
+
+RubberBandShimStatus rubberband_shim_set_expected_input_duration(RubberBandState state, unsigned int samples)
+{
+    return guarded([&] { rubberband_set_expected_input_duration(state, samples); });
+}
+
+RubberBandShimStatus rubberband_shim_set_max_process_size(RubberBandState state, unsigned int samples)
+{
+    return guarded([&] { rubberband_set_max_process_size(state, samples); });
+}
+
+RubberBandShimStatus rubberband_shim_set_key_frame_map(RubberBandState state,
+                                                       unsigned int keyframecount,
+                                                       unsigned int *from,
+                                                       unsigned int *to)
+{
+    return guarded([&] { rubberband_set_key_frame_map(state, keyframecount, from, to); });
+}
+
+RubberBandShimStatus rubberband_shim_study(RubberBandState state,
+                                           const float *const *input,
+                                           unsigned int samples,
+                                           int final)
+{
+    return guarded([&] { rubberband_study(state, input, samples, final); });
+}
+
+RubberBandShimStatus rubberband_shim_process(RubberBandState state,
+                                             const float *const *input,
+                                             unsigned int samples,
+                                             int final)
+{
+    return guarded([&] { rubberband_process(state, input, samples, final); });
+}
+
+RubberBandShimStatus rubberband_shim_retrieve(RubberBandState state,
+                                              float *const *output,
+                                              unsigned int samples,
+                                              unsigned int *retrieved)
+{
+    *retrieved = 0;
+    return guarded([&] { *retrieved = rubberband_retrieve(state, output, samples); });
+}
+
+RubberBandShimStatus rubberband_shim_available(RubberBandState state, int *available)
+{
+    *available = 0;
+    return guarded([&] { *available = rubberband_available(state); });
+}
+
+RubberBandShimStatus rubberband_shim_calculate_stretch(RubberBandState state)
+{
+    return guarded([&] { rubberband_calculate_stretch(state); });
+}
+
+RubberBandShimStatus rubberband_shim_live_new(unsigned int sampleRate,
+                                              unsigned int channels,
+                                              RubberBandLiveOptions options,
+                                              RubberBandLiveState *state)
+{
+    *state = nullptr;
+    return guarded([&] { *state = rubberband_live_new(sampleRate, channels, options); });
+}
+
+RubberBandShimStatus rubberband_shim_live_reset(RubberBandLiveState state)
+{
+    return guarded([&] { rubberband_live_reset(state); });
+}
+
+RubberBandShimStatus rubberband_shim_live_set_pitch_scale(RubberBandLiveState state, double scale)
+{
+    return guarded([&] { rubberband_live_set_pitch_scale(state, scale); });
+}
+
+RubberBandShimStatus rubberband_shim_live_set_formant_scale(RubberBandLiveState state, double scale)
+{
+    return guarded([&] { rubberband_live_set_formant_scale(state, scale); });
+}
+
+RubberBandShimStatus rubberband_shim_live_set_formant_option(RubberBandLiveState state,
+                                                             RubberBandLiveOptions options)
+{
+    return guarded([&] { rubberband_live_set_formant_option(state, options); });
+}
+
+RubberBandShimStatus rubberband_shim_live_shift(RubberBandLiveState state,
+                                                const float *const *input,
+                                                float *const *output)
+{
+    return guarded([&] { rubberband_live_shift(state, input, output); });
+}
+
+}
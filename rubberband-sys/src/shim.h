@@ -0,0 +1,96 @@
+#ifndef RUBBERBAND_SYS_SHIM_H
+#define RUBBERBAND_SYS_SHIM_H
+
+#include "rubberband/rubberband-c.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Exception-safe wrappers around the Rubber Band C API.
+ *
+ * The C API is implemented in C++, and its entry points may throw (e.g.
+ * std::bad_alloc when allocating). Letting an exception unwind into Rust is
+ * undefined behaviour, so each rubberband_shim_* function below calls the C
+ * API function of the same name inside try/catch, and reports the outcome as
+ * a status code instead. Values the wrapped function would return are passed
+ * back through a trailing pointer argument.
+ *
+ * rubberband_available is wrapped too, as without a processing thread it may
+ * process pending input (R2 engine) and so throw. The other getters, the debug
+ * level setters and the delete functions are not wrapped: they only read or
+ * set plain values, and destructors cannot throw.
+ */
+
+typedef enum {
+    /* The call completed. */
+    RubberBandShimOk = 0,
+    /* The call threw std::bad_alloc. */
+    RubberBandShimOutOfMemory = 1,
+    /* The call threw std::logic_error, e.g. std::invalid_argument. */
+    RubberBandShimInvalidArgument = 2,
+    /* The call threw any other exception. */
+    RubberBandShimException = 3
+} RubberBandShimStatus;
+
+RubberBandShimStatus rubberband_shim_new(unsigned int sampleRate,
+                                         unsigned int channels,
+                                         RubberBandOptions options,
+                                         double initialTimeRatio,
+                                         double initialPitchScale,
+                                         RubberBandState *state);
+RubberBandShimStatus rubberband_shim_reset(RubberBandState state);
+
+RubberBandShimStatus rubberband_shim_set_time_ratio(RubberBandState state, double ratio);
+RubberBandShimStatus rubberband_shim_set_pitch_scale(RubberBandState state, double scale);
+RubberBandShimStatus rubberband_shim_set_formant_scale(RubberBandState state, double scale);
+
+RubberBandShimStatus rubberband_shim_set_transients_option(RubberBandState state, RubberBandOptions options);
+RubberBandShimStatus rubberband_shim_set_detector_option(RubberBandState state, RubberBandOptions options);
+RubberBandShimStatus rubberband_shim_set_phase_option(RubberBandState state, RubberBandOptions options);
+RubberBandShimStatus rubberband_shim_set_formant_option(RubberBandState state, RubberBandOptions options);
+RubberBandShimStatus rubberband_shim_set_pitch_option(RubberBandState state, RubberBandOptions options);
+
+RubberBandShimStatus rubberband_shim_set_expected_input_duration(RubberBandState state, unsigned int samples);
+RubberBandShimStatus rubberband_shim_set_max_process_size(RubberBandState state, unsigned int samples);
+RubberBandShimStatus rubberband_shim_set_key_frame_map(RubberBandState state,
+                                                       unsigned int keyframecount,
+                                                       unsigned int *from,
+                                                       unsigned int *to);
+
+RubberBandShimStatus rubberband_shim_study(RubberBandState state,
+                                           const float *const *input,
+                                           unsigned int samples,
+                                           int final);
+RubberBandShimStatus rubberband_shim_process(RubberBandState state,
+                                             const float *const *input,
+                                             unsigned int samples,
+                                             int final);
+RubberBandShimStatus rubberband_shim_retrieve(RubberBandState state,
+                                              float *const *output,
+                                              unsigned int samples,
+                                              unsigned int *retrieved);
+RubberBandShimStatus rubberband_shim_available(RubberBandState state, int *available);
+RubberBandShimStatus rubberband_shim_calculate_stretch(RubberBandState state);
+
+RubberBandShimStatus rubberband_shim_live_new(unsigned int sampleRate,
+                                              unsigned int channels,
+                                              RubberBandLiveOptions options,
+                                              RubberBandLiveState *state);
+RubberBandShimStatus rubberband_shim_live_reset(RubberBandLiveState state);
+
+RubberBandShimStatus rubberband_shim_live_set_pitch_scale(RubberBandLiveState state, double scale);
+RubberBandShimStatus rubberband_shim_live_set_formant_scale(RubberBandLiveState state, double scale);
+RubberBandShimStatus rubberband_shim_live_set_formant_option(RubberBandLiveState state,
+                                                             RubberBandLiveOptions options);
+
+RubberBandShimStatus rubberband_shim_live_shift(RubberBandLiveState state,
+                                                const float *const *input,
+                                                float *const *output);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,200 @@
+/// The shape of a parameter glide, set with
+/// [LiveShifter::set_glide_curve()](crate::LiveShifter::set_glide_curve()).
+///
+/// Glides advance once per processed block, so both curves are piecewise constant at the block
+/// size.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rubberband::{GlideCurve, LiveShifterBuilder};
+///
+/// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+/// shifter.set_glide_curve(GlideCurve::Exponential);
+/// shifter.set_pitch_glide(Duration::from_millis(50));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlideCurve {
+    /// Equal steps of the scale value in each block.
+    Linear,
+    /// Equal ratios in each block, i.e. equal steps in semitones for a pitch scale.
+    Exponential,
+}
+
+/// The current and target values of a parameter that may be gliding, as returned by
+/// [LiveShifter::pitch_scale()](crate::LiveShifter::pitch_scale()) and
+/// [LiveShifter::formant_scale()](crate::LiveShifter::formant_scale()).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rubberband::LiveShifterBuilder;
+///
+/// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+/// shifter.set_pitch_scale_with_glide(2.0, Duration::from_millis(100));
+///
+/// // The target is set immediately; the current value follows as blocks are processed
+/// let scale = shifter.pitch_scale();
+/// assert_eq!(scale.target, 2.0);
+/// assert_eq!(scale.current, 1.0);
+/// assert!(scale.is_gliding());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlideState {
+    /// The value applied to the most recently processed block.
+    pub current: f64,
+    /// The value the parameter is gliding toward.
+    pub target: f64,
+}
+
+impl GlideState {
+    /// Check whether the current value has not reached the target yet.
+    pub fn is_gliding(&self) -> bool {
+        self.current != self.target
+    }
+}
+
+/// A parameter value walking toward its target, one step per block.
+pub(crate) struct Ramp {
+    current: f64,
+    target: f64,
+    /// The increment (linear) or factor (exponential) applied per block.
+    step: f64,
+    curve: GlideCurve,
+    /// The number of blocks left until the target is reached.
+    remaining: u32,
+}
+
+impl Ramp {
+    pub(crate) fn new(value: f64) -> Self {
+        Self {
+            current: value,
+            target: value,
+            step: 0.0,
+            curve: GlideCurve::Linear,
+            remaining: 0,
+        }
+    }
+
+    /// Start gliding from the current value to `target` over `blocks` blocks.
+    ///
+    /// Returns the value to apply immediately if there is no glide: if `blocks` is 0, or if either
+    /// end is not positive (e.g. the automatic formant scale `0.0`), the ramp jumps to the target.
+    pub(crate) fn retarget(&mut self, target: f64, blocks: u32, curve: GlideCurve) -> Option<f64> {
+        self.target = target;
+        if blocks == 0 || self.current <= 0.0 || target <= 0.0 {
+            self.remaining = 0;
+            self.current = target;
+            return Some(target);
+        }
+
+        self.curve = curve;
+        self.remaining = blocks;
+        self.step = match curve {
+            GlideCurve::Linear => (target - self.current) / blocks as f64,
+            GlideCurve::Exponential => (target / self.current).powf(1.0 / blocks as f64),
+        };
+        None
+    }
+
+    /// Get the value for the next block if it changes, without advancing.
+    pub(crate) fn peek(&self) -> Option<f64> {
+        match (self.remaining, self.curve) {
+            (0, _) => None,
+            // Land exactly on the target, without accumulated rounding errors
+            (1, _) => Some(self.target),
+            (_, GlideCurve::Linear) => Some(self.current + self.step),
+            (_, GlideCurve::Exponential) => Some(self.current * self.step),
+        }
+    }
+
+    /// Advance by one block, returning the new value if it changed.
+    pub(crate) fn advance(&mut self) -> Option<f64> {
+        let next = self.peek()?;
+        self.remaining -= 1;
+        self.current = next;
+        Some(next)
+    }
+
+    /// Jump to the target, returning it if a glide was in progress.
+    pub(crate) fn finish(&mut self) -> Option<f64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining = 0;
+        self.current = self.target;
+        Some(self.current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    fn walk(ramp: &mut Ramp) -> Vec<f64> {
+        std::iter::from_fn(|| ramp.advance()).collect()
+    }
+
+    #[test]
+    fn test_ramp_linear() {
+        let mut ramp = Ramp::new(1.0);
+        assert_eq!(ramp.retarget(2.0, 4, GlideCurve::Linear), None);
+        assert_eq!(walk(&mut ramp), vec![1.25, 1.5, 1.75, 2.0]);
+        assert_eq!(ramp.advance(), None);
+    }
+
+    #[test]
+    fn test_ramp_exponential() {
+        let mut ramp = Ramp::new(1.0);
+        ramp.retarget(4.0, 2, GlideCurve::Exponential);
+        let values = walk(&mut ramp);
+        assert_abs_diff_eq!(values[0], 2.0, epsilon = 1e-12);
+        assert_eq!(values[1], 4.0);
+    }
+
+    #[test]
+    fn test_ramp_jumps() {
+        let mut ramp = Ramp::new(1.0);
+        assert_eq!(ramp.retarget(1.5, 0, GlideCurve::Linear), Some(1.5));
+        assert_eq!(ramp.advance(), None);
+
+        // Gliding to or from 0.0 is not possible
+        assert_eq!(ramp.retarget(0.0, 10, GlideCurve::Linear), Some(0.0));
+        assert_eq!(ramp.retarget(1.2, 10, GlideCurve::Exponential), Some(1.2));
+    }
+
+    #[test]
+    fn test_ramp_retarget_midway() {
+        let mut ramp = Ramp::new(1.0);
+        ramp.retarget(2.0, 4, GlideCurve::Linear);
+        ramp.advance();
+        ramp.advance();
+
+        // A new target glides from the current value
+        ramp.retarget(1.0, 2, GlideCurve::Linear);
+        assert_eq!(walk(&mut ramp), vec![1.25, 1.0]);
+
+        ramp.retarget(3.0, 8, GlideCurve::Linear);
+        ramp.advance();
+        assert_eq!(ramp.finish(), Some(3.0));
+        assert_eq!(ramp.finish(), None);
+    }
+
+    #[test]
+    fn test_ramp_peek() {
+        let mut ramp = Ramp::new(1.0);
+        assert_eq!(ramp.peek(), None);
+        ramp.retarget(2.0, 2, GlideCurve::Linear);
+
+        // Peeking does not move the ramp
+        assert_eq!(ramp.peek(), Some(1.5));
+        assert_eq!(ramp.peek(), Some(1.5));
+        assert_eq!(ramp.advance(), Some(1.5));
+        assert_eq!(ramp.peek(), Some(2.0));
+        assert_eq!(ramp.advance(), Some(2.0));
+        assert_eq!(ramp.peek(), None);
+    }
+}
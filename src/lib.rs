@@ -7,7 +7,7 @@
 //! - `RubberBandLiveShifter`: A real-time pitch shifter (no time stretching) designed for
 //!   fixed-size block processing with minimal latency.
 //!
-//! **This crate currently only implements bindings for the `RubberBandLiveShifter` API.**
+//! This crate wraps both, as [Stretcher] and [LiveShifter] respectively.
 //!
 //! ## RubberBand Live Shifter
 //!
@@ -27,31 +27,86 @@
 //!     mode can be configured using the [LiveShifterBuilder]. Note that some options (like window
 //!     size and channel mode) cannot be changed after the shifter is built.
 //!
+//! For a lock-free audio path, [LiveShifter::split()] separates the shifter into a
+//! [LiveShifterProcessor] for the audio thread and a cloneable [LiveShifterController] for
+//! parameter changes from other threads.
+//!
+//! If the host's buffer size differs from the block size, wrap the shifter in a
+//! [LiveShifterStream], which accepts any number of samples per call at the cost of some extra
+//! latency.
+//!
+//! When the channel count is known at compile time, [LiveShifterN] takes fixed-size channel
+//! arrays instead of slices, so the channel count is checked by the type system.
+//!
+//! Besides `f32`, the processing methods accept any [Sample] format (`f64`, `i16`, [I24], `u8`),
+//! converting through preallocated scratch buffers, with optional [Dither] for integer output.
+//!
 //! See the [LiveShifter] and [LiveShifterBuilder] documentation for more details and usage examples.
 //!
-//! ## Future Work
+//! ## RubberBand Stretcher
 //!
-//! Bindings for the `RubberBandStretcher` API may be added in the future.
+//! The [Stretcher] struct wraps the `RubberBandStretcher` C++ class. It changes the duration
+//! and/or the pitch of audio, with variable input and output sizes, in one of two modes:
+//!
+//! *   **Offline:** For a complete piece of audio, processed in two passes: the whole input is
+//!     first studied, then processed, and the output is retrieved as it becomes available.
+//! *   **Real-time:** For streaming audio, with the time ratio and pitch scale adjustable while
+//!     running. The caller feeds the amount of input the stretcher asks for and collects the
+//!     output as it becomes available.
+//!
+//! See the [Stretcher] and [StretcherBuilder] documentation for more details and usage examples.
 
+use std::any::TypeId;
+use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use atomic_float::AtomicF64;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, MutexGuard};
 use thiserror::Error;
 use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use glide::Ramp;
+use sample::DitherNoise;
+use schedule::{Schedule, ScheduleQueue, ScheduledParam};
+
+mod glide;
+mod live_fixed;
+mod live_split;
+mod live_stream;
+mod sample;
+mod schedule;
+mod stretcher;
+
+pub use glide::{GlideCurve, GlideState};
+pub use live_fixed::LiveShifterN;
+pub use live_split::{LiveShifterController, LiveShifterProcessor};
+pub use live_stream::LiveShifterStream;
+pub use sample::{Dither, I24, Sample};
+pub use schedule::ScheduledChange;
+
+pub use stretcher::{
+    Stretcher,
+    StretcherBuilder,
+    StretcherDetector,
+    StretcherEngine,
+    StretcherPhase,
+    StretcherPitchMode,
+    StretcherProcessMode,
+    StretcherThreading,
+    StretcherTransients,
+};
 
 use rubberband_sys::{
-    rubberband_live_new,
     rubberband_live_delete,
     rubberband_live_set_debug_level,
-    rubberband_live_set_pitch_scale,
-    rubberband_live_set_formant_scale,
-    rubberband_live_get_formant_scale,
-    rubberband_live_set_formant_option,
     rubberband_live_get_start_delay,
     rubberband_live_get_channel_count,
     rubberband_live_get_block_size,
-    rubberband_live_shift,
-    rubberband_live_reset,
+    rubberband_shim_live_new,
+    rubberband_shim_live_set_pitch_scale,
+    rubberband_shim_live_set_formant_scale,
+    rubberband_shim_live_set_formant_option,
+    rubberband_shim_live_shift,
+    rubberband_shim_live_reset,
     RubberBandLiveState,
     RubberBandLiveOption,
     RubberBandLiveOptions,
@@ -61,6 +116,10 @@ use rubberband_sys::{
     RubberBandLiveOption_RubberBandLiveOptionFormantPreserved as OPTION_BITS_FORMANT_PRESERVED,
     RubberBandLiveOption_RubberBandLiveOptionChannelsApart as OPTION_BITS_CHANNELS_APART,
     RubberBandLiveOption_RubberBandLiveOptionChannelsTogether as OPTION_BITS_CHANNELS_TOGETHER,
+    RubberBandShimStatus,
+    RubberBandShimStatus_RubberBandShimOk as SHIM_OK,
+    RubberBandShimStatus_RubberBandShimOutOfMemory as SHIM_OUT_OF_MEMORY,
+    RubberBandShimStatus_RubberBandShimInvalidArgument as SHIM_INVALID_ARGUMENT,
 };
 
 /// Window size options for [LiveShifter].
@@ -76,7 +135,8 @@ use rubberband_sys::{
 /// let mut shifter = LiveShifterBuilder::new(44100, 1)
 ///     .unwrap()
 ///     .window(LiveShifterWindow::Medium)
-///     .build();
+///     .build()
+///     .unwrap();
 /// ```
 #[derive(Debug, Clone, Copy)]
 pub enum LiveShifterWindow {
@@ -99,7 +159,8 @@ pub enum LiveShifterWindow {
 /// let mut shifter = LiveShifterBuilder::new(44100, 1)
 ///     .unwrap()
 ///     .formant(LiveShifterFormant::Preserved)
-///     .build();
+///     .build()
+///     .unwrap();
 ///
 /// // Change the formant option
 /// shifter.set_formant_option(LiveShifterFormant::Shifted);
@@ -125,7 +186,8 @@ pub enum LiveShifterFormant {
 /// let mut shifter = LiveShifterBuilder::new(44100, 1)
 ///     .unwrap()
 ///     .channel_mode(LiveShifterChannelMode::Together)
-///     .build();
+///     .build()
+///     .unwrap();
 /// ```
 #[derive(Debug, Clone, Copy)]
 pub enum LiveShifterChannelMode {
@@ -158,7 +220,8 @@ pub enum LiveShifterChannelMode {
 ///     .formant(LiveShifterFormant::Preserved)
 ///     .channel_mode(LiveShifterChannelMode::Apart)
 ///     .debug_level(1)
-///     .build();
+///     .build()
+///     .unwrap();
 /// ```
 pub struct LiveShifterBuilder {
     /// The sample rate of the audio.
@@ -173,9 +236,28 @@ pub struct LiveShifterBuilder {
     channel_mode: LiveShifterChannelMode,
     /// The debug level of the live pitch shifter.
     debug_level: i32,
+    /// The maximum number of pending scheduled parameter changes.
+    schedule_capacity: usize,
+    /// The dithering applied to integer sample outputs.
+    dither: Dither,
 }
 
 impl LiveShifterBuilder {
+    /// The lowest sample rate supported by [LiveShifter], in Hz.
+    ///
+    /// The live shifter runs the R3 (finer) engine, which clamps the sample rate to the range from
+    /// 8 kHz to 192 kHz, processing audio at any other rate as if it were at the nearest bound.
+    pub const MIN_SAMPLE_RATE: u32 = 8000;
+
+    /// The highest sample rate supported by [LiveShifter], in Hz. See
+    /// [MIN_SAMPLE_RATE](Self::MIN_SAMPLE_RATE).
+    pub const MAX_SAMPLE_RATE: u32 = 192000;
+
+    /// The highest channel count supported by [LiveShifter].
+    ///
+    /// The C API takes the channel count as `unsigned int`, but the engine stores it as `int`.
+    pub const MAX_CHANNELS: u32 = i32::MAX as u32;
+
     /// Create a new LiveShifterBuilder.
     ///
     /// Initializes the builder with default options:
@@ -183,16 +265,25 @@ impl LiveShifterBuilder {
     /// - Formant: [LiveShifterFormant::Shifted]
     /// - Channel Mode: [LiveShifterChannelMode::Apart]
     /// - Debug Level: 0
+    /// - Schedule Capacity: 64
+    /// - Dither: [Dither::None]
     ///
     /// # Arguments
     ///
-    /// * `sample_rate`: The sample rate of the audio (must be > 0).
-    /// * `channels`: The number of channels of the audio (must be > 0).
+    /// * `sample_rate`: The sample rate of the audio, from [MIN_SAMPLE_RATE](Self::MIN_SAMPLE_RATE)
+    ///   to [MAX_SAMPLE_RATE](Self::MAX_SAMPLE_RATE).
+    /// * `channels`: The number of channels of the audio, from 1 to [MAX_CHANNELS](Self::MAX_CHANNELS).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The sample rate is out of range ([`UnsupportedSampleRate`](RubberBandError::UnsupportedSampleRate)).
+    /// - The channel count is out of range ([`UnsupportedChannelCount`](RubberBandError::UnsupportedChannelCount)).
     pub fn new(sample_rate: u32, channels: u32) -> Result<Self, RubberBandError> {
-        if sample_rate == 0 {
+        if !(Self::MIN_SAMPLE_RATE..=Self::MAX_SAMPLE_RATE).contains(&sample_rate) {
             return Err(RubberBandError::UnsupportedSampleRate(sample_rate));
         }
-        if channels == 0 {
+        if channels == 0 || channels > Self::MAX_CHANNELS {
             return Err(RubberBandError::UnsupportedChannelCount(channels));
         }
         Ok(Self {
@@ -202,6 +293,8 @@ impl LiveShifterBuilder {
             formant: LiveShifterFormant::Shifted,
             channel_mode: LiveShifterChannelMode::Apart,
             debug_level: 0,
+            schedule_capacity: 64,
+            dither: Dither::None,
         })
     }
 
@@ -260,12 +353,47 @@ impl LiveShifterBuilder {
         self
     }
 
+    /// Set the maximum number of pending scheduled parameter changes.
+    ///
+    /// The queue used by [LiveShifter::schedule_pitch_scale()] and
+    /// [LiveShifter::schedule_formant_scale()] is allocated when the shifter is built, so that
+    /// scheduling and processing never allocate. The default is 64.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: The maximum number of pending scheduled changes.
+    pub fn schedule_capacity(mut self, capacity: usize) -> Self {
+        self.schedule_capacity = capacity;
+        self
+    }
+
+    /// Set the dithering applied when processing into integer [Sample] formats.
+    ///
+    /// This option **cannot** be changed once the [LiveShifter] instance is created.
+    /// Defaults to [Dither::None].
+    ///
+    /// # Arguments
+    ///
+    /// * `dither`: The dithering option for integer outputs.
+    pub fn dither(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
+    }
+
     /// Build the [LiveShifter] with the configured options.
     ///
     /// # Returns
     ///
     /// A new [LiveShifter] instance.
-    pub fn build(self) -> LiveShifter {
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The C++ library ran out of memory ([`OutOfMemory`](RubberBandError::OutOfMemory)) or
+    ///   failed otherwise ([`InvalidArgument`](RubberBandError::InvalidArgument),
+    ///   [`LibraryException`](RubberBandError::LibraryException)).
+    /// - The underlying C++ instance could not be created ([`CreationFailed`](RubberBandError::CreationFailed)).
+    pub fn build(self) -> Result<LiveShifter, RubberBandError> {
         let mut options: RubberBandLiveOption = 0; // Default options
         match self.window {
             LiveShifterWindow::Short => options |= OPTION_BITS_WINDOW_SHORT,
@@ -280,23 +408,35 @@ impl LiveShifterBuilder {
             LiveShifterChannelMode::Together => options |= OPTION_BITS_CHANNELS_TOGETHER,
         }
 
-        let state: RubberBandLiveState = unsafe {
-            let state = rubberband_live_new(
+        let mut state: RubberBandLiveState = std::ptr::null_mut();
+        check_status(unsafe {
+            rubberband_shim_live_new(
                 self.sample_rate,
                 self.channels,
                 options as RubberBandLiveOptions,
-            );
+                &mut state,
+            )
+        })?;
+        if state.is_null() {
+            return Err(RubberBandError::CreationFailed);
+        }
+        unsafe {
             rubberband_live_set_debug_level(state, self.debug_level);
-            state
-        };
+        }
+        let block_size = unsafe { rubberband_live_get_block_size(state) as usize };
 
-        LiveShifter {
+        Ok(LiveShifter {
             state,
-            mutex: Mutex::new(()),
+            mutex: Mutex::new(ProcessState::new(
+                self.channels as usize,
+                self.sample_rate,
+                block_size,
+                self.schedule_capacity,
+                self.dither,
+            )),
             sample_rate: self.sample_rate,
-            pitch_scale: AtomicF64::new(1.0),
-            pitch_dirty: AtomicBool::new(false),
-        }
+            params: Arc::new(LiveParams::new(self.formant, block_size, self.schedule_capacity)),
+        })
     }
 }
 
@@ -315,7 +455,7 @@ impl LiveShifterBuilder {
 ///
 /// > TL;DR:
 /// > - This wrapper guarantees that it is safe to call any method concurrently with
-/// [process](Self::process()) or [process_into](Self::process_into()) on the same instance.
+/// >   [process](Self::process()) or [process_into](Self::process_into()) on the same instance.
 /// > - It is generally safe to call other methods concurrently, but it is not guaranteed.
 ///
 /// This type implements `Send` and `Sync`.
@@ -330,30 +470,35 @@ impl LiveShifterBuilder {
 ///   ensure that only one call to `process`, `process_into`, `reset`, or `start_delay` can execute
 ///   at a time on a single `LiveShifter` instance. Concurrent calls will block or return
 ///   [`OperationInProgress`](RubberBandError::OperationInProgress).
-/// - **Pitch Changes (`set_pitch_scale`, `set_pitch_semitone`, `set_pitch_cent`):** The C++
-///   `setPitchScale` function is **not** safe to call concurrently with `shift`. This wrapper
-///   uses atomic variables to store the desired pitch scale immediately without locking the main
-///   mutex, making these Rust methods safe to call concurrently. The new pitch scale will not
-///   take effect until the next `process_into` or `start_delay` call.
-/// - **Formant Changes (`set_formant_scale`, `set_formant_option`):** The underlying C++ library
-///   guarantees that `setFormantScale` and `setFormantOption` are safe to call concurrently with
-///   processing. Therefore, these Rust methods can also be called concurrently.
+/// - **Parameter Changes (`set_pitch_scale`, `set_pitch_semitone`, `set_pitch_cent`,
+///   `set_formant_scale`, `set_formant_option`):** The C++ setters are **not** safe to call
+///   concurrently with `shift`. This wrapper uses atomic variables to publish the new values
+///   immediately without locking the main mutex, making these Rust methods safe to call
+///   concurrently. The new values will not take effect until the next `process_into` or
+///   `start_delay` call, which applies them while holding the mutex.
+/// - **Scheduled Changes (`schedule_pitch_scale`, `schedule_formant_scale`):** The changes are
+///   pushed to a lock-free queue that the processing methods drain at the start of each block, so
+///   these methods are safe to call concurrently and never wait for processing.
 /// - **State Query:**
-///   - `pitch_scale`: The thread-safety is guaranteed by this Rust wrapper.
+///   - `pitch_scale`, `formant_scale`: The thread-safety is guaranteed by this Rust wrapper.
 ///   - `start_delay`: The thread-safety is guaranteed by this Rust wrapper, but it may cause the
 ///     processing call to fail (gracefully) if called concurrently.
-///   - `formant_scale`, `channel_count`, `block_size`, etc.: Thread-safe in the C++ library.
+///   - `channel_count`, `block_size`, etc.: Thread-safe in the C++ library.
 /// - **State Reset (`reset`):** These methods acquire the same internal mutex as the
 ///   processing methods to ensure safe state modification or query, and are subject to the same
 ///   concurrency limitations as `process`.
 ///
+/// To avoid the mutex on the audio thread altogether, [split()](Self::split()) the shifter into a
+/// [LiveShifterProcessor] that processes through `&mut self`, and a cloneable
+/// [LiveShifterController] that changes the parameters from other threads.
+///
 /// # Examples
 ///
 /// ```
 /// use rubberband::LiveShifterBuilder;
 ///
 /// // Create a shifter for stereo audio at 48kHz
-/// let mut shifter = LiveShifterBuilder::new(48000, 2).unwrap().build();
+/// let mut shifter = LiveShifterBuilder::new(48000, 2).unwrap().build().unwrap();
 ///
 /// // Set pitch shift up by 2 semitones
 /// shifter.set_pitch_semitone(2.0);
@@ -376,20 +521,349 @@ impl LiveShifterBuilder {
 /// ```
 pub struct LiveShifter {
     state: *mut rubberband_sys::RubberBandLiveState_,
-    mutex: Mutex<()>,
+    mutex: Mutex<ProcessState>,
     sample_rate: u32,
-    pitch_scale: AtomicF64,
-    pitch_dirty: AtomicBool,
+    params: Arc<LiveParams>,
+}
+
+/// The state of the processing side, allocated once when the [LiveShifter] is built so that
+/// processing does not allocate. It is guarded by the processing lock.
+struct ProcessState {
+    /// Channel pointer arrays passed to the C API.
+    input_ptrs: Vec<*const f32>,
+    output_ptrs: Vec<*mut f32>,
+    /// Planar scratch buffers of `channels * block_size` samples for interleaved processing and
+    /// sample format conversion.
+    planar_input: Vec<f32>,
+    planar_output: Vec<f32>,
+    /// The dither noise source for integer outputs, if enabled.
+    dither: Option<DitherNoise>,
+    sample_rate: u32,
+    block_size: usize,
+    /// The number of frames processed since the last reset.
+    frame: u64,
+    /// Parameter changes received from the `LiveParams` queue, at future frames.
+    schedule: Schedule<ScheduledParam>,
+    /// The pitch and formant scales applied to the C++ instance, possibly gliding.
+    pitch: Ramp,
+    formant: Ramp,
+}
+
+impl ProcessState {
+    fn new(
+        channels: usize,
+        sample_rate: u32,
+        block_size: usize,
+        schedule_capacity: usize,
+        dither: Dither,
+    ) -> Self {
+        Self {
+            input_ptrs: vec![std::ptr::null(); channels],
+            output_ptrs: vec![std::ptr::null_mut(); channels],
+            planar_input: vec![0.0; channels * block_size],
+            planar_output: vec![0.0; channels * block_size],
+            dither: match dither {
+                Dither::None => None,
+                Dither::Triangular => Some(DitherNoise::new()),
+            },
+            sample_rate,
+            block_size,
+            frame: 0,
+            schedule: Schedule::with_capacity(schedule_capacity),
+            pitch: Ramp::new(1.0),
+            formant: Ramp::new(0.0),
+        }
+    }
+
+    /// Apply the parameter changes published since the last call. Changes without a glide are
+    /// applied immediately, glides start from the current value. A change the C++ library fails
+    /// to apply stays pending, so that it is retried by the next call instead of lost.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the processing of `state`.
+    unsafe fn apply_pending(&mut self, state: RubberBandLiveState, params: &LiveParams) -> Result<(), RubberBandError> {
+        let curve = params.glide_curve();
+        if params.pitch_dirty.swap(false, Ordering::Acquire) {
+            let target = params.pitch_scale.load(Ordering::Relaxed);
+            let blocks = self.glide_blocks(params.pitch_change_glide.load(Ordering::Relaxed));
+            if let Some(scale) = self.pitch.retarget(target, blocks, curve) {
+                Self::set_pitch(state, params, scale)
+                    .inspect_err(|_| params.pitch_dirty.store(true, Ordering::Release))?;
+            }
+        }
+        if params.formant_scale_dirty.swap(false, Ordering::Acquire) {
+            let target = params.formant_scale.load(Ordering::Relaxed);
+            let blocks = self.glide_blocks(params.formant_change_glide.load(Ordering::Relaxed));
+            if let Some(scale) = self.formant.retarget(target, blocks, curve) {
+                Self::set_formant(state, params, scale)
+                    .inspect_err(|_| params.formant_scale_dirty.store(true, Ordering::Release))?;
+            }
+        }
+        if params.formant_option_dirty.swap(false, Ordering::Acquire) {
+            let option_bits = if params.formant_preserved.load(Ordering::Relaxed) {
+                OPTION_BITS_FORMANT_PRESERVED
+            } else {
+                OPTION_BITS_FORMANT_SHIFTED
+            };
+            check_status(rubberband_shim_live_set_formant_option(state, option_bits as RubberBandLiveOptions))
+                .inspect_err(|_| params.formant_option_dirty.store(true, Ordering::Release))?;
+        }
+        Ok(())
+    }
+
+    /// Shift one block, after applying the scheduled changes within it, the pending parameters,
+    /// and one step of any glide in progress.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the processing of `state`, and `input` and
+    /// `output` must each point to one valid pointer to `block_size` samples per channel.
+    unsafe fn shift(
+        &mut self,
+        state: RubberBandLiveState,
+        params: &LiveParams,
+        input: *const *const f32,
+        output: *const *mut f32,
+    ) -> Result<(), RubberBandError> {
+        self.schedule.receive(&params.schedule);
+        self.schedule.drain_before(&params.schedule, self.frame + self.block_size as u64, |param| match param {
+            ScheduledParam::PitchScale(scale) => params.set_pitch_scale(scale),
+            ScheduledParam::FormantScale(scale) => params.set_formant_scale(scale),
+        });
+        self.apply_pending(state, params)?;
+        // Only advance the glides once the C++ library has taken the new value, so that a failed
+        // step is retried by the next block instead of skipped
+        if let Some(scale) = self.pitch.peek() {
+            Self::set_pitch(state, params, scale)?;
+            self.pitch.advance();
+        }
+        if let Some(scale) = self.formant.peek() {
+            Self::set_formant(state, params, scale)?;
+            self.formant.advance();
+        }
+        self.shift_fixed(state, params, input, output)
+    }
+
+    /// Shift one block with the parameters as they are, leaving pending changes for later.
+    ///
+    /// # Safety
+    ///
+    /// As for [shift()](Self::shift()).
+    unsafe fn shift_fixed(
+        &mut self,
+        state: RubberBandLiveState,
+        params: &LiveParams,
+        input: *const *const f32,
+        output: *const *mut f32,
+    ) -> Result<(), RubberBandError> {
+        check_status(rubberband_shim_live_shift(state, input, output))?;
+        self.frame += self.block_size as u64;
+        params.schedule.set_frame(self.frame);
+        Ok(())
+    }
+
+    /// Shift one interleaved block through the planar scratch buffers.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the processing of `state`, and `input` and
+    /// `output` must each hold `block_size` frames for the channel count of `state`.
+    unsafe fn shift_interleaved<S: Sample>(
+        &mut self,
+        state: RubberBandLiveState,
+        params: &LiveParams,
+        input: &[S],
+        output: &mut [S],
+    ) -> Result<(), RubberBandError> {
+        let channel_count = self.input_ptrs.len();
+        let block_size = self.block_size;
+
+        for (ch, planar) in self.planar_input.chunks_exact_mut(block_size).enumerate() {
+            for (sample, frame) in planar.iter_mut().zip(input.chunks_exact(channel_count)) {
+                *sample = frame[ch].to_f32();
+            }
+        }
+        for (ptr, planar) in self.input_ptrs.iter_mut().zip(self.planar_input.chunks_exact(block_size)) {
+            *ptr = planar.as_ptr();
+        }
+        for (ptr, planar) in self.output_ptrs.iter_mut().zip(self.planar_output.chunks_exact_mut(block_size)) {
+            *ptr = planar.as_mut_ptr();
+        }
+
+        let input_ptrs = self.input_ptrs.as_ptr();
+        let output_ptrs = self.output_ptrs.as_ptr();
+        self.shift(state, params, input_ptrs, output_ptrs)?;
+
+        for (ch, planar) in self.planar_output.chunks_exact(block_size).enumerate() {
+            for (frame, sample) in output.chunks_exact_mut(channel_count).zip(planar) {
+                frame[ch] = sample::quantize(*sample, &mut self.dither);
+            }
+        }
+        Ok(())
+    }
+
+    /// Shift one planar block of any sample format. `f32` buffers are passed to the C API
+    /// directly, other formats are converted through the planar scratch buffers.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the processing of `state`, and `input` and
+    /// `output` must each hold one slice of `block_size` samples per channel of `state`.
+    unsafe fn shift_planar<S: Sample>(
+        &mut self,
+        state: RubberBandLiveState,
+        params: &LiveParams,
+        input: &[&[S]],
+        output: &mut [&mut [S]],
+    ) -> Result<(), RubberBandError> {
+        let is_f32 = TypeId::of::<S>() == TypeId::of::<f32>();
+        let block_size = self.block_size;
+
+        let inputs = self.input_ptrs.iter_mut().zip(input).zip(self.planar_input.chunks_exact_mut(block_size));
+        for ((ptr, slice), planar) in inputs {
+            if is_f32 {
+                // `S` is `f32`, so the slice can be passed on as is
+                *ptr = slice.as_ptr() as *const f32;
+            } else {
+                for (sample, value) in planar.iter_mut().zip(slice.iter()) {
+                    *sample = value.to_f32();
+                }
+                *ptr = planar.as_ptr();
+            }
+        }
+        let outputs = self.output_ptrs.iter_mut().zip(output.iter_mut()).zip(self.planar_output.chunks_exact_mut(block_size));
+        for ((ptr, slice), planar) in outputs {
+            *ptr = if is_f32 { slice.as_mut_ptr() as *mut f32 } else { planar.as_mut_ptr() };
+        }
+
+        let input_ptrs = self.input_ptrs.as_ptr();
+        let output_ptrs = self.output_ptrs.as_ptr();
+        self.shift(state, params, input_ptrs, output_ptrs)?;
+
+        if !is_f32 {
+            for (slice, planar) in output.iter_mut().zip(self.planar_output.chunks_exact(block_size)) {
+                for (value, sample) in slice.iter_mut().zip(planar) {
+                    *value = sample::quantize(*sample, &mut self.dither);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Shift one block in place. The input is copied (and converted from other formats) into the
+    /// planar input scratch buffer first, so that the C API never reads from the buffers it writes
+    /// to. `f32` output is written to the buffers directly.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the processing of `state`, and `buffers` must hold
+    /// one slice of `block_size` samples per channel of `state`.
+    unsafe fn shift_in_place<S: Sample>(
+        &mut self,
+        state: RubberBandLiveState,
+        params: &LiveParams,
+        buffers: &mut [&mut [S]],
+    ) -> Result<(), RubberBandError> {
+        let is_f32 = TypeId::of::<S>() == TypeId::of::<f32>();
+        let block_size = self.block_size;
+
+        let inputs = self.input_ptrs.iter_mut().zip(buffers.iter()).zip(self.planar_input.chunks_exact_mut(block_size));
+        for ((ptr, buffer), planar) in inputs {
+            for (sample, value) in planar.iter_mut().zip(buffer.iter()) {
+                *sample = value.to_f32();
+            }
+            *ptr = planar.as_ptr();
+        }
+        let outputs = self.output_ptrs.iter_mut().zip(buffers.iter_mut()).zip(self.planar_output.chunks_exact_mut(block_size));
+        for ((ptr, buffer), planar) in outputs {
+            *ptr = if is_f32 { buffer.as_mut_ptr() as *mut f32 } else { planar.as_mut_ptr() };
+        }
+
+        let input_ptrs = self.input_ptrs.as_ptr();
+        let output_ptrs = self.output_ptrs.as_ptr();
+        self.shift(state, params, input_ptrs, output_ptrs)?;
+
+        if !is_f32 {
+            for (buffer, planar) in buffers.iter_mut().zip(self.planar_output.chunks_exact(block_size)) {
+                for (value, sample) in buffer.iter_mut().zip(planar) {
+                    *value = sample::quantize(*sample, &mut self.dither);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Shift one block given as arrays of `CH` channels. For `f32`, the channel pointer arrays are
+    /// built on the stack; other formats are converted as in [shift_planar()](Self::shift_planar()).
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the processing of `state`, which must have `CH`
+    /// channels, and each slice of `input` and `output` must hold `block_size` samples.
+    unsafe fn shift_array<S: Sample, const CH: usize>(
+        &mut self,
+        state: RubberBandLiveState,
+        params: &LiveParams,
+        input: &[&[S]; CH],
+        output: &mut [&mut [S]; CH],
+    ) -> Result<(), RubberBandError> {
+        if TypeId::of::<S>() != TypeId::of::<f32>() {
+            return self.shift_planar(state, params, input, output);
+        }
+        // `S` is `f32`, so the slices can be passed on as is
+        let input_ptrs: [*const f32; CH] = std::array::from_fn(|ch| input[ch].as_ptr() as *const f32);
+        let output_ptrs: [*mut f32; CH] = std::array::from_fn(|ch| output[ch].as_mut_ptr() as *mut f32);
+        self.shift(state, params, input_ptrs.as_ptr(), output_ptrs.as_ptr())
+    }
+
+    /// Clear the frame counter and the scheduled changes, and finish any glide in progress.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the processing of `state`.
+    unsafe fn reset(&mut self, state: RubberBandLiveState, params: &LiveParams) -> Result<(), RubberBandError> {
+        self.frame = 0;
+        self.schedule.clear(&params.schedule);
+        params.schedule.set_frame(0);
+        if let Some(scale) = self.pitch.finish() {
+            Self::set_pitch(state, params, scale)?;
+        }
+        if let Some(scale) = self.formant.finish() {
+            Self::set_formant(state, params, scale)?;
+        }
+        Ok(())
+    }
+
+    /// Convert a glide time in seconds to a number of blocks.
+    fn glide_blocks(&self, seconds: f64) -> u32 {
+        (seconds * self.sample_rate as f64 / self.block_size as f64).round() as u32
+    }
+
+    unsafe fn set_pitch(state: RubberBandLiveState, params: &LiveParams, scale: f64) -> Result<(), RubberBandError> {
+        check_status(rubberband_shim_live_set_pitch_scale(state, scale))?;
+        params.pitch_current.store(scale, Ordering::Relaxed);
+        Ok(())
+    }
+
+    unsafe fn set_formant(state: RubberBandLiveState, params: &LiveParams, scale: f64) -> Result<(), RubberBandError> {
+        check_status(rubberband_shim_live_set_formant_scale(state, scale))?;
+        params.formant_current.store(scale, Ordering::Relaxed);
+        Ok(())
+    }
 }
 
 /// Error types for this crate.
 #[derive(Debug, Error)]
 pub enum RubberBandError {
-    /// The sample rate must be greater than 0.
+    /// The sample rate must be greater than 0, and within
+    /// [LiveShifterBuilder::MIN_SAMPLE_RATE] and [LiveShifterBuilder::MAX_SAMPLE_RATE] for
+    /// [LiveShifter].
     #[error("Unsupported sample rate: {0}")]
     UnsupportedSampleRate(u32),
 
-    /// The number of channels must be greater than 0.
+    /// The number of channels must be greater than 0, and at most
+    /// [LiveShifterBuilder::MAX_CHANNELS] for [LiveShifter].
     #[error("Unsupported channel count: {0}")]
     UnsupportedChannelCount(u32),
 
@@ -400,6 +874,13 @@ pub enum RubberBandError {
         actual: usize,
     },
 
+    /// An interleaved buffer must hold exactly one block of samples for every channel.
+    #[error("Inconsistent interleaved buffer length: expected {expected}, got {actual}")]
+    InconsistentBufferLength {
+        expected: usize,
+        actual: usize,
+    },
+
     /// Each channel must have exactly the same number of samples as the shifter's block size.
     #[error("Inconsistent block size for channel {channel}: expected {expected}, got {actual}")]
     InconsistentBlockSize {
@@ -411,6 +892,85 @@ pub enum RubberBandError {
     /// An operation (process or reset) is already in progress.
     #[error("Operation (process or reset) already in progress")]
     OperationInProgress,
+
+    /// The time ratio must be a finite number greater than 0.
+    #[error("Invalid time ratio: {0}")]
+    InvalidTimeRatio(f64),
+
+    /// The pitch scale must be a finite number greater than 0.
+    #[error("Invalid pitch scale: {0}")]
+    InvalidPitchScale(f64),
+
+    /// The formant scale must be a finite number greater than 0, or exactly 0 for automatic
+    /// formant scaling.
+    #[error("Invalid formant scale: {0}")]
+    InvalidFormantScale(f64),
+
+    /// The underlying C++ instance could not be created.
+    #[error("Failed to create the Rubber Band instance")]
+    CreationFailed,
+
+    /// The C++ library ran out of memory (it threw `std::bad_alloc`).
+    #[error("The Rubber Band library ran out of memory")]
+    OutOfMemory,
+
+    /// The C++ library rejected an argument or call (it threw `std::logic_error`).
+    #[error("The Rubber Band library rejected an invalid argument")]
+    InvalidArgument,
+
+    /// The C++ library threw an unexpected exception.
+    #[error("The Rubber Band library threw an unexpected exception")]
+    LibraryException,
+
+    /// The named operation is only allowed before processing starts.
+    #[error("Cannot call {0} after processing has started")]
+    AlreadyProcessing(&'static str),
+
+    /// Studying was started, but processing began before the final block was studied.
+    #[error("Processing started before the final block was studied")]
+    StudyIncomplete,
+
+    /// The final block has already been passed in.
+    #[error("The final block has already been passed in")]
+    AlreadyFinished,
+
+    /// The named operation is only available in offline mode.
+    #[error("{0} is only available in offline mode")]
+    OfflineOnly(&'static str),
+
+    /// The named option is ignored by the stretcher's engine, so it cannot be set.
+    #[error("The {option} option has no effect with the {engine:?} engine")]
+    UnsupportedByEngine {
+        option: &'static str,
+        engine: StretcherEngine,
+    },
+
+    /// The named operation is only available in real-time mode.
+    #[error("{0} is only available in real-time mode")]
+    RealTimeOnly(&'static str),
+
+    /// The number of samples exceeds what the stretcher accepts.
+    #[error("Too many samples: limit {limit}, got {actual}")]
+    TooManySamples {
+        limit: usize,
+        actual: usize,
+    },
+
+    /// The library was built with the `no-threading` feature, so it cannot use worker threads.
+    #[error("Threading is disabled by the `no-threading` feature")]
+    ThreadingUnavailable,
+
+    /// The queue of scheduled parameter changes is full.
+    #[error("Scheduled change queue is full (capacity {0})")]
+    ScheduleFull(usize),
+
+    /// The key frames must be strictly increasing and within the input duration, which must be
+    /// known.
+    #[error("Invalid key frame at index {index}: {reason}")]
+    InvalidKeyFrameMap {
+        index: usize,
+        reason: &'static str,
+    },
 }
 
 impl LiveShifter {
@@ -433,48 +993,132 @@ impl LiveShifter {
     ///
     /// # Arguments
     ///
-    /// * `scale`: The desired pitch scale (ratio).
+    /// * `scale`: The desired pitch scale (ratio). This is not validated; use
+    ///   [try_set_pitch_scale()](Self::try_set_pitch_scale()) for values that may be out of range.
     ///
     /// # Examples
     ///
     /// ```
     /// use rubberband::LiveShifterBuilder;
     ///
-    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build();
+    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
     ///
     /// // Shift up by one octave
     /// shifter.set_pitch_scale(2.0);
     /// ```
     pub fn set_pitch_scale(&self, scale: f64) {
-        self.pitch_scale.store(scale, Ordering::Relaxed);
-        self.pitch_dirty.store(true, Ordering::Relaxed);
+        self.params.set_pitch_scale(scale);
+    }
+
+    /// Set the pitch scale of the [LiveShifter], checking that it is valid.
+    ///
+    /// Works as [set_pitch_scale()](Self::set_pitch_scale()), but rejects values that the C++
+    /// library cannot handle instead of passing them on.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale`: The desired pitch scale (ratio).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidPitchScale`](RubberBandError::InvalidPitchScale) if the scale is not a
+    /// finite number greater than 0. The pitch scale is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::{LiveShifterBuilder, RubberBandError};
+    ///
+    /// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+    ///
+    /// assert!(shifter.try_set_pitch_scale(1.5).is_ok());
+    /// assert!(matches!(
+    ///     shifter.try_set_pitch_scale(f64::NAN),
+    ///     Err(RubberBandError::InvalidPitchScale(_))
+    /// ));
+    /// assert_eq!(shifter.pitch_scale().target, 1.5);
+    /// ```
+    pub fn try_set_pitch_scale(&self, scale: f64) -> Result<(), RubberBandError> {
+        check_pitch_scale(scale)?;
+        self.params.set_pitch_scale(scale);
+        Ok(())
+    }
+
+    /// Set the pitch scale, gliding to it over the given time.
+    ///
+    /// The pitch scale walks from its current value to `scale` by one step per processed block,
+    /// along the [glide curve](Self::set_glide_curve()), instead of jumping. A new change during a
+    /// glide starts from wherever the glide has reached. This overrides the default
+    /// [pitch glide](Self::set_pitch_glide()) for this change only.
+    ///
+    /// This method uses atomic operations and is safe to call concurrently with processing or
+    /// other methods.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale`: The desired pitch scale (ratio).
+    /// * `glide`: The glide time, rounded to whole blocks. Zero jumps immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rubberband::LiveShifterBuilder;
+    ///
+    /// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+    ///
+    /// // Glide up by one octave over 200ms
+    /// shifter.set_pitch_scale_with_glide(2.0, Duration::from_millis(200));
+    /// ```
+    pub fn set_pitch_scale_with_glide(&self, scale: f64, glide: Duration) {
+        self.params.set_pitch_scale_with_glide(scale, glide.as_secs_f64());
+    }
+
+    /// Set the default glide time for pitch scale changes.
+    ///
+    /// This applies to subsequent [set_pitch_scale()](Self::set_pitch_scale()),
+    /// [set_pitch_semitone()](Self::set_pitch_semitone()), [set_pitch_cent()](Self::set_pitch_cent())
+    /// and [scheduled](Self::schedule_pitch_scale()) changes. The default is zero, i.e. changes
+    /// jump at the next block.
+    ///
+    /// # Arguments
+    ///
+    /// * `glide`: The glide time, rounded to whole blocks.
+    pub fn set_pitch_glide(&self, glide: Duration) {
+        self.params.pitch_glide.store(glide.as_secs_f64(), Ordering::Relaxed);
+    }
+
+    /// Get the default glide time for pitch scale changes.
+    pub fn pitch_glide(&self) -> Duration {
+        Duration::from_secs_f64(self.params.pitch_glide.load(Ordering::Relaxed))
     }
 
-    /// Get the current target pitch scale of the [LiveShifter].
+    /// Get the current and target pitch scale of the [LiveShifter].
     ///
-    /// Note that the actual pitch scale applied during processing might slightly lag if
-    /// `set_pitch_scale` was called very recently from another thread.
+    /// The target is the value most recently set. The current value is the one applied to the
+    /// most recently processed block, which lags behind the target while gliding, or until the
+    /// next processing call.
     ///
     /// # Returns
     ///
-    /// The current target pitch scale ratio.
+    /// The current and target pitch scale ratios.
     ///
     /// # Examples
     ///
     /// ```
     /// use rubberband::LiveShifterBuilder;
     ///
-    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build();
+    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
     ///
     /// // Initially no pitch shift
-    /// assert_eq!(shifter.pitch_scale(), 1.0);
+    /// assert_eq!(shifter.pitch_scale().target, 1.0);
     ///
     /// // Shift up by one octave
     /// shifter.set_pitch_scale(2.0);
-    /// assert_eq!(shifter.pitch_scale(), 2.0);
+    /// assert_eq!(shifter.pitch_scale().target, 2.0);
     /// ```
-    pub fn pitch_scale(&self) -> f64 {
-        self.pitch_scale.load(Ordering::Relaxed)
+    pub fn pitch_scale(&self) -> GlideState {
+        self.params.pitch_scale()
     }
 
     /// Set the pitch shift in semitones.
@@ -495,7 +1139,7 @@ impl LiveShifter {
     /// ```
     /// use rubberband::LiveShifterBuilder;
     ///
-    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build();
+    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
     ///
     /// // Shift up by one octave (12 semitones)
     /// shifter.set_pitch_semitone(12.0);
@@ -508,9 +1152,9 @@ impl LiveShifter {
         self.set_pitch_scale(scale);
     }
 
-    /// Get the current pitch shift in semitones.
+    /// Get the target pitch shift in semitones.
     ///
-    /// Calculates the shift based on the current value returned by [pitch_scale()](Self::pitch_scale()).
+    /// Calculates the shift based on the target value returned by [pitch_scale()](Self::pitch_scale()).
     ///
     /// # Returns
     ///
@@ -522,7 +1166,7 @@ impl LiveShifter {
     /// use approx::assert_abs_diff_eq;
     /// use rubberband::LiveShifterBuilder;
     ///
-    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build();
+    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
     ///
     /// // Initially no pitch shift
     /// assert_eq!(shifter.pitch_semitone(), 0.0);
@@ -533,7 +1177,7 @@ impl LiveShifter {
     /// ```
     pub fn pitch_semitone(&self) -> f64 {
         // Convert pitch ratio to semitones: semitones = 12 * log2(ratio)
-        12.0 * self.pitch_scale().log2()
+        12.0 * self.pitch_scale().target.log2()
     }
 
     /// Set the pitch shift in cents.
@@ -554,7 +1198,7 @@ impl LiveShifter {
     /// ```
     /// use rubberband::LiveShifterBuilder;
     ///
-    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build();
+    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
     ///
     /// // Fine-tune up by 5 cents
     /// shifter.set_pitch_cent(5.0);
@@ -568,9 +1212,9 @@ impl LiveShifter {
         self.set_pitch_scale(scale);
     }
 
-    /// Get the current pitch shift in cents.
+    /// Get the target pitch shift in cents.
     ///
-    /// Calculates the shift based on the current value returned by [pitch_scale()](Self::pitch_scale()).
+    /// Calculates the shift based on the target value returned by [pitch_scale()](Self::pitch_scale()).
     ///
     /// # Returns
     ///
@@ -582,7 +1226,7 @@ impl LiveShifter {
     /// use approx::assert_abs_diff_eq;
     /// use rubberband::LiveShifterBuilder;
     ///
-    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build();
+    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
     ///
     /// // Fine-tune up by 5 cents
     /// shifter.set_pitch_cent(105.0);
@@ -590,7 +1234,7 @@ impl LiveShifter {
     /// ```
     pub fn pitch_cent(&self) -> f64 {
         // Convert pitch ratio to cents: cents = 1200 * log2(ratio)
-        1200.0 * self.pitch_scale().log2()
+        1200.0 * self.pitch_scale().target.log2()
     }
 
     /// Set the formant scale of the [LiveShifter].
@@ -607,31 +1251,101 @@ impl LiveShifter {
     /// This is typically used for special effects. For standard formant preservation, use
     /// [LiveShifterBuilder::formant()] or [set_formant_option()](Self::set_formant_option()) instead.
     ///
-    /// This method is thread-safe and can be called concurrently with processing.
+    /// Like [set_pitch_scale()](Self::set_pitch_scale()), this method uses atomic operations and
+    /// is safe to call concurrently with processing or other methods. The change will take effect
+    /// on the next processing call.
     ///
     /// # Arguments
     ///
-    /// * `scale`: The desired formant scale, or `0.0` for automatic behavior.
+    /// * `scale`: The desired formant scale, or `0.0` for automatic behavior. This is not
+    ///   validated; use [try_set_formant_scale()](Self::try_set_formant_scale()) for values that
+    ///   may be out of range.
     pub fn set_formant_scale(&self, scale: f64) {
-        unsafe {
-            rubberband_live_set_formant_scale(self.state, scale);
-        }
+        self.params.set_formant_scale(scale);
+    }
+
+    /// Set the formant scale of the [LiveShifter], checking that it is valid.
+    ///
+    /// Works as [set_formant_scale()](Self::set_formant_scale()), but rejects values that the C++
+    /// library cannot handle instead of passing them on.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale`: The desired formant scale, or `0.0` for automatic behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidFormantScale`](RubberBandError::InvalidFormantScale) if the scale is
+    /// negative or not finite. The formant scale is left unchanged.
+    pub fn try_set_formant_scale(&self, scale: f64) -> Result<(), RubberBandError> {
+        check_formant_scale(scale)?;
+        self.params.set_formant_scale(scale);
+        Ok(())
+    }
+
+    /// Set the formant scale, gliding to it over the given time.
+    ///
+    /// Works as [set_pitch_scale_with_glide()](Self::set_pitch_scale_with_glide()). Changes to or
+    /// from `0.0` (automatic) cannot glide and jump immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale`: The desired formant scale, or `0.0` for automatic behavior.
+    /// * `glide`: The glide time, rounded to whole blocks. Zero jumps immediately.
+    pub fn set_formant_scale_with_glide(&self, scale: f64, glide: Duration) {
+        self.params.set_formant_scale_with_glide(scale, glide.as_secs_f64());
+    }
+
+    /// Set the default glide time for formant scale changes.
+    ///
+    /// Works as [set_pitch_glide()](Self::set_pitch_glide()), for
+    /// [set_formant_scale()](Self::set_formant_scale()) and
+    /// [scheduled](Self::schedule_formant_scale()) changes. The default is zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `glide`: The glide time, rounded to whole blocks.
+    pub fn set_formant_glide(&self, glide: Duration) {
+        self.params.formant_glide.store(glide.as_secs_f64(), Ordering::Relaxed);
+    }
+
+    /// Get the default glide time for formant scale changes.
+    pub fn formant_glide(&self) -> Duration {
+        Duration::from_secs_f64(self.params.formant_glide.load(Ordering::Relaxed))
+    }
+
+    /// Set the curve of pitch and formant scale glides.
+    ///
+    /// The default is [GlideCurve::Linear]. The curve is read when a glide starts, so it does not
+    /// affect a glide in progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `curve`: The desired [GlideCurve].
+    pub fn set_glide_curve(&self, curve: GlideCurve) {
+        self.params.set_glide_curve(curve);
+    }
+
+    /// Get the curve of pitch and formant scale glides.
+    pub fn glide_curve(&self) -> GlideCurve {
+        self.params.glide_curve()
     }
 
-    /// Get the currently set formant scale of the [LiveShifter].
+    /// Get the current and target formant scale of the [LiveShifter].
     ///
-    /// Returns `0.0` if automatic scaling (based on the [LiveShifterFormant] option) is active.
-    /// Otherwise, returns the value explicitly set by [set_formant_scale()](Self::set_formant_scale()).
+    /// A value of `0.0` means automatic scaling (based on the [LiveShifterFormant] option).
+    /// Otherwise, the target is the value explicitly set by
+    /// [set_formant_scale()](Self::set_formant_scale()). As with
+    /// [pitch_scale()](Self::pitch_scale()), the current value is the one applied to the most
+    /// recently processed block.
     ///
     /// This method is thread-safe.
     ///
     /// # Returns
     ///
-    /// The explicitly set formant scale, or `0.0` for automatic.
-    pub fn formant_scale(&self) -> f64 {
-        unsafe {
-            rubberband_live_get_formant_scale(self.state)
-        }
+    /// The current and target formant scales, `0.0` meaning automatic.
+    pub fn formant_scale(&self) -> GlideState {
+        self.params.formant_scale()
     }
 
     /// Set the formant preservation option of the [LiveShifter].
@@ -639,7 +1353,8 @@ impl LiveShifter {
     /// Allows changing whether formants are shifted with the pitch or preserved after the
     /// shifter has been created.
     ///
-    /// This method is thread-safe and can be called concurrently with processing.
+    /// This method uses atomic operations and is safe to call concurrently with processing or
+    /// other methods. The change will take effect on the next processing call.
     ///
     /// # Arguments
     ///
@@ -650,22 +1365,13 @@ impl LiveShifter {
     /// ```
     /// use rubberband::{LiveShifterBuilder, LiveShifterFormant};
     ///
-    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build();
+    /// let mut shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
     ///
     /// // Change the formant option
     /// shifter.set_formant_option(LiveShifterFormant::Preserved);
     /// ```
     pub fn set_formant_option(&self, option: LiveShifterFormant) {
-        let option_bits = match option {
-            LiveShifterFormant::Shifted => OPTION_BITS_FORMANT_SHIFTED,
-            LiveShifterFormant::Preserved => OPTION_BITS_FORMANT_PRESERVED,
-        };
-        unsafe {
-            rubberband_live_set_formant_option(
-                self.state,
-                option_bits as RubberBandLiveOptions,
-            );
-        }
+        self.params.set_formant_option(option);
     }
 
     /// Get the start delay (in samples per channel) of the [LiveShifter].
@@ -683,12 +1389,11 @@ impl LiveShifter {
     ///
     /// The start delay in samples per channel.
     pub fn start_delay(&self) -> u32 {
-        let _guard = self.mutex.lock();
+        let mut process = self.mutex.lock();
         unsafe {
-            if self.pitch_dirty.load(Ordering::Relaxed) {
-                rubberband_live_set_pitch_scale(self.state, self.pitch_scale.load(Ordering::Relaxed));
-                self.pitch_dirty.store(false, Ordering::Relaxed);
-            }
+            // A change the C++ library fails to apply stays pending, to be retried and reported
+            // by the next processing call
+            let _ = process.apply_pending(self.state, &self.params);
             rubberband_live_get_start_delay(self.state)
         }
     }
@@ -725,19 +1430,20 @@ impl LiveShifter {
 
     /// Process a single block of audio samples, allocating and returning the output.
     ///
-    /// This is a convenience wrapper around [process_into()](Self::process_into()).
+    /// This is a convenience wrapper around [process_into()](Self::process_into()). As it
+    /// allocates the output, prefer `process_into` on a realtime audio thread.
     ///
     /// # Arguments
     ///
-    /// * `input`: A slice of slices (`&[&[f32]]`), where each inner slice represents one channel
-    ///            of audio data.
+    /// * `input`: A slice of slices (`&[&[S]]`), where each inner slice represents one channel
+    ///   of audio data, in any [Sample] format.
     ///   - The number of inner slices must equal [channel_count()](Self::channel_count()).
     ///   - The length of each inner slice must equal [block_size()](Self::block_size()).
     ///
     /// # Returns
     ///
-    /// A `Vec<Vec<f32>>` containing the processed audio data, with the same channel count and
-    /// block size as the input.
+    /// A `Vec<Vec<S>>` containing the processed audio data, with the same channel count, block
+    /// size and sample format as the input.
     ///
     /// # Errors
     ///
@@ -745,9 +1451,9 @@ impl LiveShifter {
     /// - Input channel count or block size is incorrect ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
     /// - A concurrent call to `process`, `process_into`, `reset`, or `start_delay` is in progress
     ///   on the same instance ([`OperationInProgress`](RubberBandError::OperationInProgress)).
-    pub fn process(&self, input: &[&[f32]]) -> Result<Vec<Vec<f32>>, RubberBandError> {
-        let mut output = vec![vec![0.0; input[0].len()]; input.len()];
-        let mut output_slices: Vec<&mut [f32]> = output
+    pub fn process<S: Sample>(&self, input: &[&[S]]) -> Result<Vec<Vec<S>>, RubberBandError> {
+        let mut output = vec![vec![S::from_f32(0.0); input[0].len()]; input.len()];
+        let mut output_slices: Vec<&mut [S]> = output
             .iter_mut()
             .map(|slice| slice.as_mut_slice())
             .collect();
@@ -757,17 +1463,23 @@ impl LiveShifter {
 
     /// Process a single block of audio samples using pre-allocated output buffers.
     ///
-    /// This is the primary processing method and avoids allocations. It wraps the underlying
-    /// `shift` C++ method, adding checks and handling pitch scale updates.
+    /// This is the primary processing method and performs no heap allocation, so it is safe to
+    /// call from a realtime audio thread. It wraps the underlying `shift` C++ method, adding
+    /// checks and handling pitch scale updates.
+    ///
+    /// `f32` buffers are passed to the C++ library directly. Other [Sample] formats are converted
+    /// through scratch buffers allocated when the shifter was built, with
+    /// [dither](LiveShifterBuilder::dither()) for integer outputs if enabled.
     ///
     /// The input and output buffers must not alias or overlap.
     ///
     /// # Arguments
     ///
-    /// * `input`: A slice of slices (`&[&[f32]]`) representing the input audio block.
+    /// * `input`: A slice of slices (`&[&[S]]`) representing the input audio block.
     ///   - Must have `channel_count` inner slices.
     ///   - Each inner slice must have `block_size` samples.
-    /// * `output`: A mutable slice of mutable slices (`&mut [&mut [f32]]`) for the output.
+    /// * `output`: A mutable slice of mutable slices (`&mut [&mut [S]]`) for the output, in the
+    ///   same sample format as the input.
     ///   - Must have `channel_count` inner slices.
     ///   - Each inner slice must have `block_size` samples. The contents will be overwritten.
     ///
@@ -777,92 +1489,654 @@ impl LiveShifter {
     /// - Input/output channel count or block size is incorrect ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
     /// - A concurrent call to `process`, `process_into`, `reset`, or `start_delay` is in progress
     ///   on the same instance ([`OperationInProgress`](RubberBandError::OperationInProgress)).
-    pub fn process_into(&self, input: &[&[f32]], output: &mut [&mut [f32]]) -> Result<(), RubberBandError> {
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::{Dither, LiveShifterBuilder};
+    ///
+    /// let shifter = LiveShifterBuilder::new(44100, 1)
+    ///     .unwrap()
+    ///     .dither(Dither::Triangular)
+    ///     .build()
+    ///     .unwrap();
+    /// let block_size = shifter.block_size() as usize;
+    ///
+    /// // 16-bit samples, e.g. from a capture device
+    /// let input = vec![1000i16; block_size];
+    /// let mut output = vec![0i16; block_size];
+    /// shifter.process_into(&[&input], &mut [&mut output]).unwrap();
+    /// ```
+    pub fn process_into<S: Sample>(&self, input: &[&[S]], output: &mut [&mut [S]]) -> Result<(), RubberBandError> {
         // The underlying C++ implementation does not allow concurrent calls to `shift()`.
-        let _guard = self.mutex.try_lock();
-        if _guard.is_none() {
-            return Err(RubberBandError::OperationInProgress);
-        }
+        let mut process = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
 
-        let channel_count = self.channel_count() as usize;
-        if input.len() != channel_count {
-            return Err(RubberBandError::InconsistentChannelCount {
-                expected: channel_count,
-                actual: input.len(),
-            });
+        check_block(input, output, self.channel_count() as usize, self.block_size() as usize)?;
+
+        // Reuse the pointer arrays and scratch buffers allocated at build time
+        unsafe {
+            process.shift_planar(self.state, &self.params, input, output)
         }
-        if output.len() != channel_count {
-            return Err(RubberBandError::InconsistentChannelCount {
-                expected: channel_count,
-                actual: output.len(),
-            });
+    }
+
+    /// Process a single block of audio samples in place, overwriting the input with the output.
+    ///
+    /// Works as [process_into()](Self::process_into()), for hosts that pass one buffer per
+    /// channel to read from and write back into. The input is copied into a scratch buffer
+    /// allocated when the shifter was built before the block is shifted, so this performs no heap
+    /// allocation and is safe to call from a realtime audio thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffers`: A mutable slice of mutable slices (`&mut [&mut [S]]`) holding the input audio
+    ///   block, in any [Sample] format.
+    ///   - Must have `channel_count` inner slices.
+    ///   - Each inner slice must have `block_size` samples. The contents will be overwritten with
+    ///     the output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The channel count or block size is incorrect ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - A concurrent call to `process`, `process_into`, `reset`, or `start_delay` is in progress
+    ///   on the same instance ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::LiveShifterBuilder;
+    ///
+    /// let shifter = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+    /// let block_size = shifter.block_size() as usize;
+    ///
+    /// // One buffer per channel, as given by the host
+    /// let mut left = vec![0.1f32; block_size];
+    /// let mut right = vec![-0.1f32; block_size];
+    /// shifter.process_in_place(&mut [&mut left, &mut right]).unwrap();
+    /// ```
+    pub fn process_in_place<S: Sample>(&self, buffers: &mut [&mut [S]]) -> Result<(), RubberBandError> {
+        let mut process = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        check_channels(buffers, self.channel_count() as usize, self.block_size() as usize)?;
+
+        unsafe {
+            process.shift_in_place(self.state, &self.params, buffers)
         }
+    }
 
-        let block_size = self.block_size() as usize;
-        for ch in 0..channel_count {
-            if input[ch].len() != block_size {
-                return Err(RubberBandError::InconsistentBlockSize {
-                    channel: ch,
-                    expected: block_size,
-                    actual: input[ch].len(),
-                });
-            }
-            if output[ch].len() != block_size {
+    /// Process a single interleaved block of audio samples, allocating and returning the output.
+    ///
+    /// This is a convenience wrapper around
+    /// [process_interleaved_into()](Self::process_interleaved_into()). As it allocates the output,
+    /// prefer `process_interleaved_into` on a realtime audio thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: The interleaved input block, with
+    ///   [channel_count()](Self::channel_count()) × [block_size()](Self::block_size()) samples in
+    ///   any [Sample] format.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<S>` containing the processed audio data, interleaved like the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] in the same cases as
+    /// [process_interleaved_into()](Self::process_interleaved_into()).
+    pub fn process_interleaved<S: Sample>(&self, input: &[S]) -> Result<Vec<S>, RubberBandError> {
+        let mut output = vec![S::from_f32(0.0); input.len()];
+        self.process_interleaved_into(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Process a single interleaved block of audio samples using a pre-allocated output buffer.
+    ///
+    /// Works as [process_into()](Self::process_into()), for the interleaved buffers given by most
+    /// audio APIs and file formats (frame by frame, with one sample per channel in each frame).
+    /// The samples are deinterleaved (and converted from other [Sample] formats) into planar
+    /// scratch buffers allocated when the shifter was built, so this performs no heap allocation
+    /// and is safe to call from a realtime audio thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: The interleaved input block, with
+    ///   [channel_count()](Self::channel_count()) × [block_size()](Self::block_size()) samples.
+    /// * `output`: The interleaved output block, of the same length and sample format as the
+    ///   input. The contents will be overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The input or output length is not `channel_count * block_size` ([`InconsistentBufferLength`](RubberBandError::InconsistentBufferLength)).
+    /// - A concurrent call to `process`, `process_into`, `reset`, or `start_delay` is in progress
+    ///   on the same instance ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::LiveShifterBuilder;
+    ///
+    /// let shifter = LiveShifterBuilder::new(48000, 2).unwrap().build().unwrap();
+    /// let block_size = shifter.block_size() as usize;
+    ///
+    /// // Left and right samples alternate
+    /// let input = vec![0.1f32; 2 * block_size];
+    /// let mut output = vec![0.0f32; 2 * block_size];
+    /// shifter.process_interleaved_into(&input, &mut output).unwrap();
+    /// ```
+    pub fn process_interleaved_into<S: Sample>(&self, input: &[S], output: &mut [S]) -> Result<(), RubberBandError> {
+        let mut process = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        check_interleaved(input, output, self.channel_count() as usize, self.block_size() as usize)?;
+
+        unsafe {
+            process.shift_interleaved(self.state, &self.params, input, output)
+        }
+    }
+
+    /// Process a whole buffer of any length, returning output aligned with the input.
+    ///
+    /// This is an offline convenience method that takes care of the fixed block size and the
+    /// [start delay](Self::start_delay()): the input is padded with silence up to whole blocks
+    /// long enough to flush the delayed tail, the leading start delay is trimmed from the output,
+    /// and the result has exactly the same length as the input and is sample-aligned with it.
+    ///
+    /// The shifter is [reset](Self::reset()) before processing, so any state from previous calls,
+    /// including scheduled changes, is discarded. Parameter changes made before the call are then
+    /// applied, with any glide completed at once, and the parameters stay fixed for the whole
+    /// buffer, as a change within it would move the start delay. Changes made while this call is
+    /// running, e.g. from another thread, are held back until the next processing call.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: A slice of slices (`&[&[f32]]`), where each inner slice represents one channel
+    ///   of audio data.
+    ///   - The number of inner slices must equal [channel_count()](Self::channel_count()).
+    ///   - All inner slices must have the same length, which can be anything.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<Vec<f32>>` containing the processed audio data, with the same channel count and
+    /// length as the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - Input channel count is incorrect, or the channels differ in length ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - A concurrent call to `process`, `process_into`, `process_buffer`, `reset`, or
+    ///   `start_delay` is in progress on the same instance ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::LiveShifterBuilder;
+    ///
+    /// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+    /// shifter.set_pitch_semitone(-2.0);
+    ///
+    /// let input = vec![0.1f32; 10000];
+    /// let output = shifter.process_buffer(&[&input]).unwrap();
+    /// assert_eq!(output[0].len(), input.len());
+    /// ```
+    pub fn process_buffer(&self, input: &[&[f32]]) -> Result<Vec<Vec<f32>>, RubberBandError> {
+        let mut process = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        let channel_count = self.channel_count() as usize;
+        if input.len() != channel_count {
+            return Err(RubberBandError::InconsistentChannelCount {
+                expected: channel_count,
+                actual: input.len(),
+            });
+        }
+        let length = input[0].len();
+        for (ch, slice) in input.iter().enumerate() {
+            if slice.len() != length {
                 return Err(RubberBandError::InconsistentBlockSize {
                     channel: ch,
-                    expected: block_size,
-                    actual: output[ch].len(),
+                    expected: length,
+                    actual: slice.len(),
                 });
             }
         }
 
-        let input_ptrs: Vec<*const f32> = input
-            .iter()
-            .map(|slice| slice.as_ptr())
-            .collect();
-        let output_ptrs: Vec<*mut f32> = output
-            .iter_mut()
-            .map(|slice| slice.as_mut_ptr())
-            .collect();
+        let block_size = self.block_size() as usize;
+        let start_delay = unsafe {
+            // Resetting completes the glides started by the pending changes
+            process.apply_pending(self.state, &self.params)?;
+            process.reset(self.state, &self.params)?;
+            check_status(rubberband_shim_live_reset(self.state))?;
+            rubberband_live_get_start_delay(self.state) as usize
+        };
 
+        let mut output = vec![vec![0.0f32; length]; channel_count];
+        let mut in_block = vec![vec![0.0f32; block_size]; channel_count];
+        let mut out_block = vec![vec![0.0f32; block_size]; channel_count];
+        let mut input_ptrs: Vec<*const f32> = vec![std::ptr::null(); channel_count];
+        let mut output_ptrs: Vec<*mut f32> = vec![std::ptr::null_mut(); channel_count];
+
+        // Output sample `n` of the shifter corresponds to input sample `n - start_delay`
+        let mut pos = 0;
+        while pos < length + start_delay {
+            for (block, slice) in in_block.iter_mut().zip(input) {
+                let start = pos.min(length);
+                let end = (pos + block_size).min(length);
+                block[..end - start].copy_from_slice(&slice[start..end]);
+                block[end - start..].fill(0.0);
+            }
 
-        unsafe {
-            if self.pitch_dirty.load(Ordering::Relaxed) {
-                rubberband_live_set_pitch_scale(self.state, self.pitch_scale.load(Ordering::Relaxed));
-                self.pitch_dirty.store(false, Ordering::Relaxed);
+            // Take the pointers after filling the blocks, as writing through the blocks
+            // invalidates any pointers taken before
+            for (ptr, block) in input_ptrs.iter_mut().zip(&in_block) {
+                *ptr = block.as_ptr();
+            }
+            for (ptr, block) in output_ptrs.iter_mut().zip(&mut out_block) {
+                *ptr = block.as_mut_ptr();
+            }
+            unsafe {
+                process.shift_fixed(self.state, &self.params, input_ptrs.as_ptr(), output_ptrs.as_ptr())?;
+            }
+
+            // Copy the part of this block that falls within the aligned output
+            let start = pos.max(start_delay);
+            let end = (pos + block_size).min(length + start_delay);
+            if start < end {
+                for (slice, block) in output.iter_mut().zip(&out_block) {
+                    slice[start - start_delay..end - start_delay]
+                        .copy_from_slice(&block[start - pos..end - pos]);
+                }
             }
-            rubberband_live_shift(
-                self.state,
-                input_ptrs.as_ptr(),
-                output_ptrs.as_ptr(),
-            );
+            pos += block_size;
         }
 
-        Ok(())
+        Ok(output)
+    }
+
+    /// Take the processing lock without blocking, for a series of [shift_raw()](Self::shift_raw())
+    /// calls, and apply the pending parameter changes so that their failures are reported before
+    /// any block is shifted.
+    pub(crate) fn lock_raw(&self) -> Result<MutexGuard<'_, ProcessState>, RubberBandError> {
+        let mut process = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+        unsafe {
+            process.apply_pending(self.state, &self.params)?;
+        }
+        Ok(process)
+    }
+
+    /// Shift a single block through raw channel pointer arrays, without checks or allocation.
+    ///
+    /// # Safety
+    ///
+    /// `process` must be locked from this shifter with [lock_raw()](Self::lock_raw()), and `input`
+    /// and `output` must each point to [channel_count()](Self::channel_count()) valid pointers to
+    /// [block_size()](Self::block_size()) samples, and the buffers must not overlap.
+    pub(crate) unsafe fn shift_raw(
+        &self,
+        process: &mut ProcessState,
+        input: *const *const f32,
+        output: *const *mut f32,
+    ) -> Result<(), RubberBandError> {
+        process.shift(self.state, &self.params, input, output)
+    }
+
+    /// Schedule a pitch scale change at the given frame.
+    ///
+    /// Frames are counted from the last [reset()](Self::reset()) (or from creation), and advance
+    /// by [block_size()](Self::block_size()) with every processed block, see
+    /// [frame_position()](Self::frame_position()). The change is applied when processing the
+    /// block containing `frame`, so it takes effect up to one block early. If that block has
+    /// already been processed, the change is applied at the next block instead. The returned
+    /// [ScheduledChange] reports the frame at which the change actually takes effect.
+    ///
+    /// Scheduled changes are applied in frame order, each overriding the target value as if
+    /// [set_pitch_scale()](Self::set_pitch_scale()) were called at that block.
+    ///
+    /// This method is lock-free and does not allocate, so it may be called from any thread while
+    /// processing; the queue capacity is set by [LiveShifterBuilder::schedule_capacity()]. A
+    /// change scheduled within the block being processed at the same time is applied at the next
+    /// block, which the returned value may not reflect.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The frame at which the change should take effect.
+    /// * `scale`: The desired pitch scale (ratio).
+    ///
+    /// # Returns
+    ///
+    /// Where the change was scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The pitch scale is not a finite positive number ([`InvalidPitchScale`](RubberBandError::InvalidPitchScale)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::LiveShifterBuilder;
+    ///
+    /// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+    ///
+    /// // Shift up by 7 semitones at frame 12345
+    /// let change = shifter.schedule_pitch_scale(12345, 2.0f64.powf(7.0 / 12.0)).unwrap();
+    /// assert!(change.applied <= 12345);
+    /// assert!(change.quantization() > -(shifter.block_size() as i64));
+    /// ```
+    pub fn schedule_pitch_scale(&self, frame: u64, scale: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_pitch_scale(scale)?;
+        self.params.schedule(frame, ScheduledParam::PitchScale(scale))
+    }
+
+    /// Schedule a formant scale change at the given frame.
+    ///
+    /// Scheduling works as for [schedule_pitch_scale()](Self::schedule_pitch_scale()), with each
+    /// change applied as if [set_formant_scale()](Self::set_formant_scale()) were called at that
+    /// block.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The frame at which the change should take effect.
+    /// * `scale`: The desired formant scale, or `0.0` for automatic behavior.
+    ///
+    /// # Returns
+    ///
+    /// Where the change was scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The formant scale is negative or not finite ([`InvalidFormantScale`](RubberBandError::InvalidFormantScale)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    pub fn schedule_formant_scale(&self, frame: u64, scale: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_formant_scale(scale)?;
+        self.params.schedule(frame, ScheduledParam::FormantScale(scale))
+    }
+
+    /// Get the number of frames processed since the last [reset()](Self::reset()).
+    ///
+    /// This is the frame counter that scheduled changes are measured against.
+    ///
+    /// **Note:** This method acquires the internal processing lock, like
+    /// [start_delay()](Self::start_delay()).
+    pub fn frame_position(&self) -> u64 {
+        self.mutex.lock().frame
     }
 
     /// Reset the internal state of the [LiveShifter].
     ///
     /// This clears the internal buffers and history, effectively making the shifter behave as if
     /// it were newly created, but retaining all parameter settings (pitch scale, formant options, etc.).
+    /// The frame counter is set back to 0, and pending scheduled changes are discarded.
     ///
     /// **Note:** This method acquires the internal processing lock. Calling it concurrently with
     /// [process()](Self::process()) or [process_into()](Self::process_into()) on the same instance
     /// will block.
-    pub fn reset(&self) {
-        let _guard = self.mutex.lock();
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if the C++ library failed to reset
+    /// ([`OutOfMemory`](RubberBandError::OutOfMemory), [`InvalidArgument`](RubberBandError::InvalidArgument),
+    /// [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn reset(&self) -> Result<(), RubberBandError> {
+        let mut process = self.mutex.lock();
         unsafe {
-            rubberband_live_reset(self.state);
+            process.reset(self.state, &self.params)?;
+            check_status(rubberband_shim_live_reset(self.state))
         }
     }
+
+    /// Split the [LiveShifter] into a processor for the audio thread and a controller handle.
+    ///
+    /// The [LiveShifterProcessor] owns the shifter and processes through `&mut self`, so the hot
+    /// path takes no lock and can never fail with
+    /// [`OperationInProgress`](RubberBandError::OperationInProgress). The
+    /// [LiveShifterController] is cloneable, `Send` and `Sync`, and changes the pitch and formant
+    /// parameters from any thread without locking; the processor applies the changes at the start
+    /// of its next block.
+    ///
+    /// All settings made so far, including the frame counter and scheduled changes, are carried
+    /// over.
+    ///
+    /// # Returns
+    ///
+    /// The processor and a controller for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::LiveShifterBuilder;
+    ///
+    /// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+    /// let (mut processor, controller) = shifter.split();
+    ///
+    /// // e.g. on a UI thread
+    /// let handle = std::thread::spawn(move || controller.set_pitch_semitone(5.0));
+    /// handle.join().unwrap();
+    ///
+    /// // On the audio thread
+    /// let block_size = processor.block_size() as usize;
+    /// let input = vec![0.1f32; block_size];
+    /// let mut output = vec![0.0f32; block_size];
+    /// processor.process_into(&[&input], &mut [&mut output]).unwrap();
+    /// ```
+    pub fn split(mut self) -> (LiveShifterProcessor, LiveShifterController) {
+        // Leave a null state behind, so that dropping `self` does not delete the instance
+        let state = std::mem::replace(&mut self.state, std::ptr::null_mut());
+        let process = std::mem::replace(self.mutex.get_mut(), ProcessState::new(0, 0, 1, 0, Dither::None));
+
+        let controller = LiveShifterController::new(self.params.clone());
+        let processor = LiveShifterProcessor::new(state, self.params.clone(), process, self.sample_rate);
+        (processor, controller)
+    }
 }
 
 impl Drop for LiveShifter {
     fn drop(&mut self) {
-        unsafe { rubberband_live_delete(self.state) };
+        // The state is null if it was moved into a `LiveShifterProcessor` by `split()`
+        if !self.state.is_null() {
+            unsafe { rubberband_live_delete(self.state) };
+        }
+    }
+}
+
+/// Parameters that can be changed from any thread, and are applied to the C++ instance by the
+/// thread holding it, at the next processing call.
+struct LiveParams {
+    /// The target pitch scale.
+    pitch_scale: AtomicF64,
+    /// The glide time (in seconds) for the pending pitch scale change.
+    pitch_change_glide: AtomicF64,
+    pitch_dirty: AtomicBool,
+    /// The default glide time (in seconds) for pitch scale changes.
+    pitch_glide: AtomicF64,
+    /// The pitch scale applied to the most recent block, published by the processing thread.
+    pitch_current: AtomicF64,
+    formant_scale: AtomicF64,
+    formant_change_glide: AtomicF64,
+    formant_scale_dirty: AtomicBool,
+    formant_glide: AtomicF64,
+    formant_current: AtomicF64,
+    formant_preserved: AtomicBool,
+    formant_option_dirty: AtomicBool,
+    glide_exponential: AtomicBool,
+    /// The block size of the C++ instance, which scheduled changes are quantized to.
+    block_size: usize,
+    /// Parameter changes scheduled at future frames, received by the processing thread at the
+    /// start of each block.
+    schedule: ScheduleQueue<ScheduledParam>,
+}
+
+impl LiveParams {
+    fn new(formant: LiveShifterFormant, block_size: usize, schedule_capacity: usize) -> Self {
+        Self {
+            pitch_scale: AtomicF64::new(1.0),
+            pitch_change_glide: AtomicF64::new(0.0),
+            pitch_dirty: AtomicBool::new(false),
+            pitch_glide: AtomicF64::new(0.0),
+            pitch_current: AtomicF64::new(1.0),
+            formant_scale: AtomicF64::new(0.0),
+            formant_change_glide: AtomicF64::new(0.0),
+            formant_scale_dirty: AtomicBool::new(false),
+            formant_glide: AtomicF64::new(0.0),
+            formant_current: AtomicF64::new(0.0),
+            formant_preserved: AtomicBool::new(matches!(formant, LiveShifterFormant::Preserved)),
+            formant_option_dirty: AtomicBool::new(false),
+            glide_exponential: AtomicBool::new(false),
+            block_size,
+            schedule: ScheduleQueue::with_capacity(schedule_capacity),
+        }
+    }
+
+    /// Schedule a parameter change at `frame`.
+    fn schedule(&self, frame: u64, param: ScheduledParam) -> Result<ScheduledChange, RubberBandError> {
+        self.schedule.push(frame, param)?;
+        // The change is applied at the start of its block, or at the next block if that one has
+        // already been processed
+        let block_start = frame - frame % self.block_size as u64;
+        let applied = block_start.max(self.schedule.frame());
+        Ok(ScheduledChange { requested: frame, applied })
+    }
+
+    fn set_pitch_scale(&self, scale: f64) {
+        self.set_pitch_scale_with_glide(scale, self.pitch_glide.load(Ordering::Relaxed));
+    }
+
+    fn set_pitch_scale_with_glide(&self, scale: f64, glide: f64) {
+        self.pitch_scale.store(scale, Ordering::Relaxed);
+        self.pitch_change_glide.store(glide, Ordering::Relaxed);
+        self.pitch_dirty.store(true, Ordering::Release);
+    }
+
+    fn pitch_scale(&self) -> GlideState {
+        GlideState {
+            current: self.pitch_current.load(Ordering::Relaxed),
+            target: self.pitch_scale.load(Ordering::Relaxed),
+        }
+    }
+
+    fn set_formant_scale(&self, scale: f64) {
+        self.set_formant_scale_with_glide(scale, self.formant_glide.load(Ordering::Relaxed));
+    }
+
+    fn set_formant_scale_with_glide(&self, scale: f64, glide: f64) {
+        self.formant_scale.store(scale, Ordering::Relaxed);
+        self.formant_change_glide.store(glide, Ordering::Relaxed);
+        self.formant_scale_dirty.store(true, Ordering::Release);
+    }
+
+    fn formant_scale(&self) -> GlideState {
+        GlideState {
+            current: self.formant_current.load(Ordering::Relaxed),
+            target: self.formant_scale.load(Ordering::Relaxed),
+        }
+    }
+
+    fn set_formant_option(&self, option: LiveShifterFormant) {
+        self.formant_preserved.store(matches!(option, LiveShifterFormant::Preserved), Ordering::Relaxed);
+        self.formant_option_dirty.store(true, Ordering::Release);
+    }
+
+    fn set_glide_curve(&self, curve: GlideCurve) {
+        self.glide_exponential.store(curve == GlideCurve::Exponential, Ordering::Relaxed);
+    }
+
+    fn glide_curve(&self) -> GlideCurve {
+        if self.glide_exponential.load(Ordering::Relaxed) {
+            GlideCurve::Exponential
+        } else {
+            GlideCurve::Linear
+        }
+    }
+}
+
+/// Map the status of a `rubberband_shim_*` call to an error.
+fn check_status(status: RubberBandShimStatus) -> Result<(), RubberBandError> {
+    match status {
+        SHIM_OK => Ok(()),
+        SHIM_OUT_OF_MEMORY => Err(RubberBandError::OutOfMemory),
+        SHIM_INVALID_ARGUMENT => Err(RubberBandError::InvalidArgument),
+        _ => Err(RubberBandError::LibraryException),
     }
 }
 
+/// Check that the pitch scale is a finite number greater than 0.
+fn check_pitch_scale(scale: f64) -> Result<(), RubberBandError> {
+    if !scale.is_finite() || scale <= 0.0 {
+        return Err(RubberBandError::InvalidPitchScale(scale));
+    }
+    Ok(())
+}
+
+/// Check that the formant scale is a finite number greater than 0, or 0 for automatic scaling.
+fn check_formant_scale(scale: f64) -> Result<(), RubberBandError> {
+    if !scale.is_finite() || scale < 0.0 {
+        return Err(RubberBandError::InvalidFormantScale(scale));
+    }
+    Ok(())
+}
+
+/// Check that the interleaved input and output hold `block_size` frames of `channel_count`
+/// channels.
+fn check_interleaved<S>(
+    input: &[S],
+    output: &[S],
+    channel_count: usize,
+    block_size: usize,
+) -> Result<(), RubberBandError> {
+    let expected = channel_count * block_size;
+    for actual in [input.len(), output.len()] {
+        if actual != expected {
+            return Err(RubberBandError::InconsistentBufferLength { expected, actual });
+        }
+    }
+    Ok(())
+}
+
+/// Check that the input and output are blocks of `block_size` samples for `channel_count`
+/// channels.
+fn check_block<S>(
+    input: &[&[S]],
+    output: &[&mut [S]],
+    channel_count: usize,
+    block_size: usize,
+) -> Result<(), RubberBandError> {
+    check_channels(input, channel_count, block_size)?;
+    check_channels(output, channel_count, block_size)
+}
+
+/// Check that `buffers` is a block of `block_size` samples for `channel_count` channels.
+fn check_channels<S, B: AsRef<[S]>>(
+    buffers: &[B],
+    channel_count: usize,
+    block_size: usize,
+) -> Result<(), RubberBandError> {
+    if buffers.len() != channel_count {
+        return Err(RubberBandError::InconsistentChannelCount {
+            expected: channel_count,
+            actual: buffers.len(),
+        });
+    }
+
+    for (ch, buffer) in buffers.iter().enumerate() {
+        let actual = buffer.as_ref().len();
+        if actual != block_size {
+            return Err(RubberBandError::InconsistentBlockSize {
+                channel: ch,
+                expected: block_size,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
 unsafe impl Send for LiveShifter {}
 unsafe impl Sync for LiveShifter {}
 
@@ -885,7 +2159,8 @@ mod tests {
             let shifter = LiveShifterBuilder::new(sample_rate, 1)
                 .unwrap()
                 .window(window)
-                .build();
+                .build()
+                .unwrap();
             assert_eq!(shifter.start_delay(), expected_delay);
         }
 
@@ -907,7 +2182,8 @@ mod tests {
         for sample_rate in [16000, 44100, 48000, 96000, 192000] {
             let shifter = LiveShifterBuilder::new(sample_rate, 1)
                 .unwrap()
-                .build();
+                .build()
+                .unwrap();
             assert_eq!(shifter.block_size(), 512);
         }
     }
@@ -916,10 +2192,11 @@ mod tests {
     fn test_process_invalid_channels() {
         let shifter = LiveShifterBuilder::new(44100, 2)
             .unwrap()
-            .build();
+            .build()
+            .unwrap();
 
         let block_size = shifter.block_size() as usize;
-        let input = vec![vec![0.0f32; block_size]];  // Only 1 channel for 2-channel shifter
+        let input = [vec![0.0f32; block_size]];  // Only 1 channel for 2-channel shifter
         let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
 
         assert!(matches!(
@@ -932,10 +2209,11 @@ mod tests {
     fn test_process_invalid_block_size() {
         let shifter = LiveShifterBuilder::new(44100, 1)
             .unwrap()
-            .build();
+            .build()
+            .unwrap();
 
         let wrong_size = 64;  // Using arbitrary small size
-        let input = vec![vec![0.0f32; wrong_size]];
+        let input = [vec![0.0f32; wrong_size]];
         let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
 
         assert!(matches!(
@@ -948,10 +2226,11 @@ mod tests {
     fn test_process_valid_input() {
         let shifter = LiveShifterBuilder::new(44100, 1)
             .unwrap()
-            .build();
+            .build()
+            .unwrap();
 
         let block_size = shifter.block_size() as usize;
-        let input = vec![vec![0.5f32; block_size]];
+        let input = [vec![0.5f32; block_size]];
         let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
 
         let result = shifter.process(&input_slices);
@@ -966,11 +2245,12 @@ mod tests {
     fn test_process_into() {
         let shifter = LiveShifterBuilder::new(44100, 2)
             .unwrap()
-            .build();
+            .build()
+            .unwrap();
 
         let block_size = shifter.block_size() as usize;
-        let input = vec![vec![0.5f32; block_size], vec![0.3f32; block_size]];
-        let mut output = vec![vec![0.0f32; block_size], vec![0.0f32; block_size]];
+        let input = [vec![0.5f32; block_size], vec![0.3f32; block_size]];
+        let mut output = [vec![0.0f32; block_size], vec![0.0f32; block_size]];
 
         let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
         let mut output_slices: Vec<&mut [f32]> = output.iter_mut().map(|v| v.as_mut_slice()).collect();
@@ -982,14 +2262,15 @@ mod tests {
     fn test_reset() {
         let shifter = LiveShifterBuilder::new(44100, 1)
             .unwrap()
-            .build();
+            .build()
+            .unwrap();
 
         // Process several blocks to cover the start delay
         let block_size = shifter.block_size();
         let start_delay = shifter.start_delay();
-        let blocks_for_delay = (start_delay + block_size - 1) / block_size;
+        let blocks_for_delay = start_delay.div_ceil(block_size);
 
-        let input = vec![vec![0.5f32; block_size as usize]];
+        let input = [vec![0.5f32; block_size as usize]];
         let mut output = vec![vec![0.0f32; block_size as usize]; 1];
         let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
 
@@ -1000,7 +2281,7 @@ mod tests {
         assert!(!output[0].iter().all(|x| *x == 0.0));
 
         // After reset, the internal state is cleared and the output should be all zeros
-        shifter.reset();
+        shifter.reset().unwrap();
         {
             let mut output_slices: Vec<&mut [f32]> = output.iter_mut().map(|v| v.as_mut_slice()).collect();
             shifter.process_into(&input_slices, &mut output_slices).unwrap();
@@ -1008,6 +2289,373 @@ mod tests {
         assert!(output[0].iter().all(|x| *x == 0.0));
     }
 
+    #[test]
+    fn test_process_buffer_length() {
+        let shifter = LiveShifterBuilder::new(44100, 2)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let block_size = shifter.block_size() as usize;
+        for length in [0, 1, block_size - 1, block_size, 3 * block_size + 17, 44100] {
+            let input = [vec![0.5f32; length], vec![-0.5f32; length]];
+            let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+
+            let output = shifter.process_buffer(&input_slices).unwrap();
+            assert_eq!(output.len(), 2);
+            assert!(output.iter().all(|ch| ch.len() == length), "Output length mismatch for input length {}", length);
+        }
+
+        let input = [vec![0.5f32; 100], vec![0.5f32; 99]];
+        let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+        assert!(matches!(
+            shifter.process_buffer(&input_slices),
+            Err(RubberBandError::InconsistentBlockSize { channel: 1, expected: 100, actual: 99 })
+        ));
+    }
+
+    #[test]
+    fn test_process_buffer_alignment() {
+        for scale in [0.8, 1.0, 1.25] {
+            let shifter = LiveShifterBuilder::new(44100, 1)
+                .unwrap()
+                .build()
+                .unwrap();
+            shifter.set_pitch_scale(scale);
+
+            // A short burst in the middle of the buffer should come out at the same position
+            let length = 20000;
+            let center = 10000;
+            let input: Vec<f32> = (0..length)
+                .map(|n| {
+                    let t = (n as f32 - center as f32) / 200.0;
+                    (-t * t).exp() * (n as f32 * 0.1).sin()
+                })
+                .collect();
+
+            let output = shifter.process_buffer(&[&input]).unwrap();
+            let energy_center = |signal: &[f32]| {
+                let total: f32 = signal.iter().map(|x| x * x).sum();
+                signal.iter().enumerate().map(|(n, x)| n as f32 * x * x).sum::<f32>() / total
+            };
+            let offset = energy_center(&output[0]) - energy_center(&input);
+            assert!(offset.abs() < 64.0, "Output misaligned by {} samples for scale {}", offset, scale);
+        }
+    }
+
+    #[test]
+    fn test_process_buffer_completes_glides() {
+        let shifter = LiveShifterBuilder::new(44100, 1)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        // A pending glide is completed before processing, rather than running through the buffer
+        shifter.set_pitch_scale_with_glide(1.5, Duration::from_secs(1));
+        let input = vec![0.1f32; 10000];
+        shifter.process_buffer(&[&input]).unwrap();
+        assert_eq!(shifter.pitch_scale().current, 1.5);
+    }
+
+    #[test]
+    fn test_schedule_quantization() {
+        let shifter = LiveShifterBuilder::new(44100, 1)
+            .unwrap()
+            .build()
+            .unwrap();
+        let block_size = shifter.block_size() as u64;
+
+        // Changes are applied at the start of the block containing their frame
+        let change = shifter.schedule_pitch_scale(12345, 1.5).unwrap();
+        assert_eq!(change.requested, 12345);
+        assert_eq!(change.applied, 12345 / block_size * block_size);
+        assert_eq!(change.quantization(), -((12345 % block_size) as i64));
+
+        let change = shifter.schedule_formant_scale(block_size * 3, 1.2).unwrap();
+        assert_eq!(change.quantization(), 0);
+
+        // Changes for blocks already processed are applied at the next block
+        let input = [vec![0.0f32; block_size as usize]];
+        let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+        shifter.process(&input_slices).unwrap();
+        shifter.process(&input_slices).unwrap();
+        assert_eq!(shifter.frame_position(), block_size * 2);
+
+        let change = shifter.schedule_pitch_scale(10, 0.8).unwrap();
+        assert_eq!(change.applied, block_size * 2);
+        assert_eq!(change.quantization(), (block_size * 2 - 10) as i64);
+    }
+
+    #[test]
+    fn test_schedule_applied_at_block() {
+        let shifter = LiveShifterBuilder::new(44100, 1)
+            .unwrap()
+            .build()
+            .unwrap();
+        let block_size = shifter.block_size() as u64;
+        let input = [vec![0.0f32; block_size as usize]];
+        let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+
+        shifter.schedule_pitch_scale(block_size * 2 + 100, 1.5).unwrap();
+        shifter.schedule_pitch_scale(block_size * 2 + 200, 2.0).unwrap();
+        shifter.schedule_formant_scale(block_size, 1.25).unwrap();
+
+        shifter.process(&input_slices).unwrap();
+        assert_eq!(shifter.pitch_scale().target, 1.0);
+        assert_eq!(shifter.formant_scale().target, 0.0);
+
+        shifter.process(&input_slices).unwrap();
+        assert_eq!(shifter.pitch_scale().target, 1.0);
+        assert_eq!(shifter.formant_scale().target, 1.25);
+
+        // Both changes fall in the third block; the later one wins
+        shifter.process(&input_slices).unwrap();
+        assert_eq!(shifter.pitch_scale().target, 2.0);
+    }
+
+    #[test]
+    fn test_schedule_reset_and_capacity() {
+        let shifter = LiveShifterBuilder::new(44100, 1)
+            .unwrap()
+            .schedule_capacity(2)
+            .build()
+            .unwrap();
+        let block_size = shifter.block_size() as u64;
+
+        shifter.schedule_pitch_scale(block_size, 1.5).unwrap();
+        shifter.schedule_pitch_scale(block_size * 2, 2.0).unwrap();
+        assert!(matches!(
+            shifter.schedule_pitch_scale(block_size * 3, 0.5),
+            Err(RubberBandError::ScheduleFull(2))
+        ));
+
+        // Reset clears the queue and the frame counter
+        let input = [vec![0.0f32; block_size as usize]];
+        let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+        shifter.process(&input_slices).unwrap();
+        shifter.reset().unwrap();
+        assert_eq!(shifter.frame_position(), 0);
+        for _ in 0..4 {
+            shifter.process(&input_slices).unwrap();
+        }
+        assert_eq!(shifter.pitch_scale().target, 1.0);
+        assert!(shifter.schedule_pitch_scale(0, 1.5).is_ok());
+    }
+
+    #[test]
+    fn test_schedule_while_processing() {
+        let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        let block_size = shifter.block_size() as u64;
+
+        // Scheduling does not take the processing lock
+        let process = shifter.lock_raw().unwrap();
+        let change = shifter.schedule_pitch_scale(block_size, 1.5).unwrap();
+        assert_eq!(change.applied, block_size);
+        drop(process);
+
+        let input = [vec![0.0f32; block_size as usize]];
+        let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+        shifter.process(&input_slices).unwrap();
+        assert_eq!(shifter.pitch_scale().target, 1.0);
+        shifter.process(&input_slices).unwrap();
+        assert_eq!(shifter.pitch_scale().target, 1.5);
+    }
+
+    #[test]
+    fn test_process_interleaved() {
+        let planar = LiveShifterBuilder::new(44100, 2)
+            .unwrap()
+            .build()
+            .unwrap();
+        let interleaved = LiveShifterBuilder::new(44100, 2)
+            .unwrap()
+            .build()
+            .unwrap();
+        let block_size = planar.block_size() as usize;
+
+        for block in 0..4 {
+            let left: Vec<f32> = (0..block_size).map(|i| ((block * block_size + i) as f32 * 0.05).sin()).collect();
+            let right: Vec<f32> = left.iter().map(|x| -0.5 * x).collect();
+            let input: Vec<f32> = left.iter().zip(&right).flat_map(|(&l, &r)| [l, r]).collect();
+
+            let expected = planar.process(&[&left, &right]).unwrap();
+            let output = interleaved.process_interleaved(&input).unwrap();
+            for (i, frame) in output.chunks_exact(2).enumerate() {
+                assert_eq!(frame[0], expected[0][i]);
+                assert_eq!(frame[1], expected[1][i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_process_sample_formats() {
+        let build = || LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        let (reference, wide, narrow) = (build(), build(), build());
+        let block_size = reference.block_size() as usize;
+
+        for block in 0..4 {
+            let input: Vec<f32> = (0..block_size)
+                .map(|i| 0.5 * ((block * block_size + i) as f32 * 0.05).sin())
+                .collect();
+            let input_f64: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+            let input_i16: Vec<i16> = input.iter().map(|&x| i16::from_f32(x)).collect();
+
+            let expected = reference.process(&[&input]).unwrap();
+            let output_f64 = wide.process(&[&input_f64]).unwrap();
+            let output_i16 = narrow.process(&[&input_i16]).unwrap();
+            for i in 0..block_size {
+                approx::assert_abs_diff_eq!(output_f64[0][i], expected[0][i] as f64, epsilon = 1e-6);
+                approx::assert_abs_diff_eq!(output_i16[0][i].to_f32(), expected[0][i], epsilon = 2.0 / 32768.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_process_in_place() {
+        let build = || LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+        let (reference, shifter) = (build(), build());
+        let block_size = shifter.block_size() as usize;
+
+        for block in 0..4 {
+            let left: Vec<f32> = (0..block_size).map(|i| ((block * block_size + i) as f32 * 0.02).sin()).collect();
+            let right: Vec<f32> = left.iter().map(|x| 0.5 * x).collect();
+            let expected = reference.process(&[&left, &right]).unwrap();
+
+            let (mut in_place_left, mut in_place_right) = (left.clone(), right.clone());
+            shifter.process_in_place(&mut [&mut in_place_left, &mut in_place_right]).unwrap();
+            assert_eq!(in_place_left, expected[0]);
+            assert_eq!(in_place_right, expected[1]);
+        }
+
+        let mut short = vec![0.0f32; block_size - 1];
+        let mut full = vec![0.0f32; block_size];
+        assert!(matches!(
+            shifter.process_in_place(&mut [&mut full, &mut short]),
+            Err(RubberBandError::InconsistentBlockSize { channel: 1, .. })
+        ));
+        assert!(matches!(
+            shifter.process_in_place(&mut [&mut full]),
+            Err(RubberBandError::InconsistentChannelCount { expected: 2, actual: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_process_interleaved_length() {
+        let shifter = LiveShifterBuilder::new(44100, 2)
+            .unwrap()
+            .build()
+            .unwrap();
+        let block_size = shifter.block_size() as usize;
+
+        let input = vec![0.0f32; block_size];
+        assert!(matches!(
+            shifter.process_interleaved(&input),
+            Err(RubberBandError::InconsistentBufferLength { expected, actual })
+                if expected == 2 * block_size && actual == block_size
+        ));
+
+        let input = vec![0.0f32; 2 * block_size];
+        let mut output = vec![0.0f32; 2 * block_size + 1];
+        assert!(matches!(
+            shifter.process_interleaved_into(&input, &mut output),
+            Err(RubberBandError::InconsistentBufferLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_check_status() {
+        assert!(check_status(SHIM_OK).is_ok());
+        assert!(matches!(check_status(SHIM_OUT_OF_MEMORY), Err(RubberBandError::OutOfMemory)));
+        assert!(matches!(check_status(SHIM_INVALID_ARGUMENT), Err(RubberBandError::InvalidArgument)));
+        assert!(matches!(
+            check_status(rubberband_sys::RubberBandShimStatus_RubberBandShimException),
+            Err(RubberBandError::LibraryException)
+        ));
+    }
+
+    #[test]
+    fn test_builder_bounds() {
+        for sample_rate in [0, LiveShifterBuilder::MIN_SAMPLE_RATE - 1, LiveShifterBuilder::MAX_SAMPLE_RATE + 1] {
+            assert!(matches!(
+                LiveShifterBuilder::new(sample_rate, 1),
+                Err(RubberBandError::UnsupportedSampleRate(rate)) if rate == sample_rate
+            ));
+        }
+        for channels in [0, LiveShifterBuilder::MAX_CHANNELS + 1, u32::MAX] {
+            assert!(matches!(
+                LiveShifterBuilder::new(44100, channels),
+                Err(RubberBandError::UnsupportedChannelCount(count)) if count == channels
+            ));
+        }
+        assert!(LiveShifterBuilder::new(44100, LiveShifterBuilder::MAX_CHANNELS).is_ok());
+
+        for sample_rate in [LiveShifterBuilder::MIN_SAMPLE_RATE, LiveShifterBuilder::MAX_SAMPLE_RATE] {
+            let shifter = LiveShifterBuilder::new(sample_rate, 2).unwrap().build().unwrap();
+            assert_eq!(shifter.sample_rate(), sample_rate);
+        }
+    }
+
+    #[test]
+    fn test_try_setters() {
+        let shifter = LiveShifterBuilder::new(44100, 1)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        for scale in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(shifter.try_set_pitch_scale(scale), Err(RubberBandError::InvalidPitchScale(_))));
+            assert!(matches!(shifter.schedule_pitch_scale(0, scale), Err(RubberBandError::InvalidPitchScale(_))));
+        }
+        for scale in [-0.5, f64::NAN, f64::NEG_INFINITY] {
+            assert!(matches!(shifter.try_set_formant_scale(scale), Err(RubberBandError::InvalidFormantScale(_))));
+            assert!(matches!(shifter.schedule_formant_scale(0, scale), Err(RubberBandError::InvalidFormantScale(_))));
+        }
+        assert_eq!(shifter.pitch_scale().target, 1.0);
+        assert_eq!(shifter.formant_scale().target, 0.0);
+
+        shifter.try_set_pitch_scale(0.5).unwrap();
+        shifter.try_set_formant_scale(1.5).unwrap();
+        assert_eq!(shifter.pitch_scale().target, 0.5);
+        assert_eq!(shifter.formant_scale().target, 1.5);
+
+        // 0.0 selects automatic formant scaling
+        shifter.try_set_formant_scale(0.0).unwrap();
+        assert_eq!(shifter.formant_scale().target, 0.0);
+    }
+
+    #[test]
+    fn test_pitch_glide() {
+        let shifter = LiveShifterBuilder::new(44100, 1)
+            .unwrap()
+            .build()
+            .unwrap();
+        let block_size = shifter.block_size() as usize;
+        let block_duration = Duration::from_secs_f64(block_size as f64 / 44100.0);
+
+        // Glide over 4 blocks
+        shifter.set_pitch_scale_with_glide(2.0, block_duration * 4);
+        assert_eq!(shifter.pitch_scale(), GlideState { current: 1.0, target: 2.0 });
+
+        let input = [vec![0.0f32; block_size]];
+        let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+        let mut previous = 1.0;
+        for _ in 0..4 {
+            shifter.process(&input_slices).unwrap();
+            let current = shifter.pitch_scale().current;
+            assert!(current > previous, "Pitch scale did not increase: {} -> {}", previous, current);
+            previous = current;
+        }
+        assert_eq!(shifter.pitch_scale(), GlideState { current: 2.0, target: 2.0 });
+
+        // The default glide applies to plain setters, and reset jumps to the target
+        shifter.set_pitch_glide(block_duration * 8);
+        shifter.set_pitch_scale(1.0);
+        shifter.process(&input_slices).unwrap();
+        assert!(shifter.pitch_scale().is_gliding());
+        shifter.reset().unwrap();
+        assert_eq!(shifter.pitch_scale(), GlideState { current: 1.0, target: 1.0 });
+    }
+
     #[test]
     fn test_pitch_shift_frequency() {
         use std::f32::consts::PI;
@@ -1016,13 +2664,14 @@ mod tests {
         // Set the pitch scale to 2.0 (one octave up)
         let shifter = LiveShifterBuilder::new(sample_rate, 1)
             .unwrap()
-            .build();
+            .build()
+            .unwrap();
         shifter.set_pitch_scale(2.0);
 
         // Calculate number of blocks needed to cover start delay plus some extra blocks for measurement
         let block_size = shifter.block_size() as usize;
         let start_delay = shifter.start_delay() as usize;
-        let blocks_for_delay = (start_delay + block_size - 1) / block_size; // Round up division
+        let blocks_for_delay = start_delay.div_ceil(block_size);
         let measurement_blocks = 5; // Number of blocks to use for frequency measurement
         let total_blocks = blocks_for_delay + measurement_blocks;
 
@@ -1034,9 +2683,9 @@ mod tests {
 
         for block in 0..total_blocks {
             let mut input = vec![0.0f32; block_size];
-            for i in 0..block_size {
+            for (i, sample) in input.iter_mut().enumerate() {
                 let n = block * block_size + i;
-                input[i] = (omega * n as f32).sin();
+                *sample = (omega * n as f32).sin();
             }
             let input_slice = &input[..];
             let output = shifter.process(&[input_slice]).unwrap();
@@ -0,0 +1,187 @@
+use crate::{check_block, LiveShifter, RubberBandError, Sample};
+
+/// A [LiveShifter] with a channel count fixed at compile time.
+///
+/// [LiveShifter::process_into()] takes the channels as slices, so it checks their number on
+/// every call and keeps the channel pointer arrays passed to the C API on the heap. This wrapper
+/// takes arrays of exactly `CH` channels instead (`&[&[S]; CH]` and `&mut [&mut [S]; CH]`): a
+/// wrong channel count is a compile error, and for `f32` the pointer arrays are built on the
+/// stack. Only the block size is checked at runtime.
+///
+/// The wrapped shifter is reached through [shifter()](Self::shifter()), e.g. to change the pitch
+/// scale. Processing shares its lock, so the thread safety guarantees of [LiveShifter] apply.
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{LiveShifterBuilder, LiveShifterN};
+///
+/// let shifter = LiveShifterBuilder::new(48000, 2).unwrap().build().unwrap();
+/// let stereo = LiveShifterN::<2>::new(shifter).unwrap();
+/// stereo.shifter().set_pitch_semitone(-3.0);
+///
+/// let block_size = stereo.block_size() as usize;
+/// let input_l = vec![0.1f32; block_size];
+/// let input_r = vec![-0.1f32; block_size];
+/// let mut output_l = vec![0.0f32; block_size];
+/// let mut output_r = vec![0.0f32; block_size];
+///
+/// stereo
+///     .process_into(&[&input_l, &input_r], &mut [&mut output_l, &mut output_r])
+///     .unwrap();
+/// ```
+pub struct LiveShifterN<const CH: usize> {
+    shifter: LiveShifter,
+    block_size: usize,
+}
+
+impl<const CH: usize> LiveShifterN<CH> {
+    /// Create a new [LiveShifterN] wrapping the given shifter.
+    ///
+    /// # Arguments
+    ///
+    /// * `shifter`: The [LiveShifter] to wrap, which must have been built for `CH` channels.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount) if the
+    /// shifter's channel count is not `CH`.
+    pub fn new(shifter: LiveShifter) -> Result<Self, RubberBandError> {
+        let channel_count = shifter.channel_count() as usize;
+        if channel_count != CH {
+            return Err(RubberBandError::InconsistentChannelCount {
+                expected: CH,
+                actual: channel_count,
+            });
+        }
+        let block_size = shifter.block_size() as usize;
+        Ok(Self { shifter, block_size })
+    }
+
+    /// Get the wrapped [LiveShifter], e.g. to change the pitch scale.
+    pub fn shifter(&self) -> &LiveShifter {
+        &self.shifter
+    }
+
+    /// Consume the wrapper and return the wrapped [LiveShifter].
+    pub fn into_inner(self) -> LiveShifter {
+        self.shifter
+    }
+
+    /// Get the number of channels, which is always `CH`.
+    pub fn channel_count(&self) -> u32 {
+        CH as u32
+    }
+
+    /// Get the required block size (in samples per channel) for processing.
+    ///
+    /// See [LiveShifter::block_size()].
+    pub fn block_size(&self) -> u32 {
+        self.block_size as u32
+    }
+
+    /// Process a single block of audio samples, allocating and returning the output.
+    ///
+    /// This is a convenience wrapper around [process_into()](Self::process_into()). As it
+    /// allocates the output, prefer `process_into` on a realtime audio thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] in the same cases as [process_into()](Self::process_into()).
+    pub fn process<S: Sample>(&self, input: &[&[S]; CH]) -> Result<[Vec<S>; CH], RubberBandError> {
+        let mut output: [Vec<S>; CH] = std::array::from_fn(|_| vec![S::from_f32(0.0); self.block_size]);
+        let mut output_slices = output.each_mut().map(|slice| slice.as_mut_slice());
+        self.process_into(input, &mut output_slices)?;
+        Ok(output)
+    }
+
+    /// Process a single block of audio samples using pre-allocated output buffers.
+    ///
+    /// Works as [LiveShifter::process_into()], without checking the channel count at runtime.
+    /// This performs no heap allocation, so it is safe to call from a realtime audio thread.
+    ///
+    /// The input and output buffers must not alias or overlap.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: One slice of `block_size` samples per channel.
+    /// * `output`: One mutable slice of `block_size` samples per channel, in the same sample format
+    ///   as the input. The contents will be overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - Input/output block size is incorrect ([`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - A concurrent call to `process`, `process_into`, `reset`, or `start_delay` is in progress
+    ///   on the wrapped shifter ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn process_into<S: Sample>(
+        &self,
+        input: &[&[S]; CH],
+        output: &mut [&mut [S]; CH],
+    ) -> Result<(), RubberBandError> {
+        let shifter = &self.shifter;
+        let mut process = shifter.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        // The channel counts match by construction, so only the block sizes are checked
+        check_block(input, output, CH, self.block_size)?;
+
+        unsafe {
+            process.shift_array(shifter.state, &shifter.params, input, output)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LiveShifterBuilder;
+
+    #[test]
+    fn test_channel_count_mismatch() {
+        let shifter = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+        assert!(matches!(
+            LiveShifterN::<1>::new(shifter),
+            Err(RubberBandError::InconsistentChannelCount { expected: 1, actual: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_matches_live_shifter() {
+        let build = || LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+        let reference = build();
+        let stereo = LiveShifterN::<2>::new(build()).unwrap();
+        reference.set_pitch_scale(1.5);
+        stereo.shifter().set_pitch_scale(1.5);
+        let block_size = stereo.block_size() as usize;
+
+        for block in 0..4 {
+            let left: Vec<f32> = (0..block_size).map(|i| ((block * block_size + i) as f32 * 0.01).sin()).collect();
+            let right: Vec<f32> = left.iter().map(|x| -x).collect();
+
+            let expected = reference.process(&[&left, &right]).unwrap();
+            let output = stereo.process(&[&left, &right]).unwrap();
+            assert_eq!(output[0], expected[0]);
+            assert_eq!(output[1], expected[1]);
+
+            // Other sample formats go through the scratch buffers
+            let left_f64: Vec<f64> = left.iter().map(|&x| x as f64).collect();
+            let right_f64: Vec<f64> = right.iter().map(|&x| x as f64).collect();
+            let expected = reference.process(&[&left_f64, &right_f64]).unwrap();
+            let output = stereo.process(&[&left_f64, &right_f64]).unwrap();
+            assert_eq!(output[0], expected[0]);
+            assert_eq!(output[1], expected[1]);
+        }
+    }
+
+    #[test]
+    fn test_invalid_block_size() {
+        let mono = LiveShifterN::<1>::new(LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap()).unwrap();
+        let input = vec![0.0f32; mono.block_size() as usize - 1];
+        assert!(matches!(
+            mono.process(&[&input]),
+            Err(RubberBandError::InconsistentBlockSize { channel: 0, .. })
+        ));
+    }
+}
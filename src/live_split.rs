@@ -0,0 +1,566 @@
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use rubberband_sys::{
+    rubberband_live_delete,
+    rubberband_live_get_block_size,
+    rubberband_live_get_channel_count,
+    rubberband_live_get_start_delay,
+    rubberband_shim_live_reset,
+    RubberBandLiveState,
+};
+
+use crate::schedule::ScheduledParam;
+use crate::{
+    check_block,
+    check_channels,
+    check_formant_scale,
+    check_interleaved,
+    check_pitch_scale,
+    check_status,
+    GlideCurve,
+    GlideState,
+    LiveParams,
+    LiveShifterFormant,
+    ProcessState,
+    RubberBandError,
+    Sample,
+    ScheduledChange,
+};
+
+/// The audio-thread half of a [LiveShifter](crate::LiveShifter), created by
+/// [LiveShifter::split()](crate::LiveShifter::split()).
+///
+/// The processor owns the underlying C++ instance. Its processing methods take `&mut self`, so
+/// exclusive access is checked at compile time instead of with a lock: processing never blocks
+/// and never fails with [`OperationInProgress`](RubberBandError::OperationInProgress).
+///
+/// Parameter changes made through the [LiveShifterController] are applied at the start of the
+/// next [process_into()](Self::process_into()) call, i.e. at block boundaries.
+///
+/// This type implements `Send`, so it can be moved to the audio thread.
+pub struct LiveShifterProcessor {
+    state: RubberBandLiveState,
+    params: Arc<LiveParams>,
+    process: ProcessState,
+    sample_rate: u32,
+    channel_count: usize,
+    block_size: usize,
+}
+
+impl LiveShifterProcessor {
+    pub(crate) fn new(
+        state: RubberBandLiveState,
+        params: Arc<LiveParams>,
+        process: ProcessState,
+        sample_rate: u32,
+    ) -> Self {
+        let (channel_count, block_size) = unsafe {
+            (
+                rubberband_live_get_channel_count(state) as usize,
+                rubberband_live_get_block_size(state) as usize,
+            )
+        };
+        Self {
+            state,
+            params,
+            process,
+            sample_rate,
+            channel_count,
+            block_size,
+        }
+    }
+
+    /// Get the sample rate of the processor.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get the number of channels the processor was configured for.
+    pub fn channel_count(&self) -> u32 {
+        self.channel_count as u32
+    }
+
+    /// Get the required block size (in samples per channel) for processing.
+    ///
+    /// See [LiveShifter::block_size()](crate::LiveShifter::block_size()).
+    pub fn block_size(&self) -> u32 {
+        self.block_size as u32
+    }
+
+    /// Get a new [LiveShifterController] for this processor.
+    pub fn controller(&self) -> LiveShifterController {
+        LiveShifterController::new(self.params.clone())
+    }
+
+    /// Get the start delay (in samples per channel) of the processor.
+    ///
+    /// Pending parameter changes are applied first, as the delay depends on the pitch scale.
+    /// See [LiveShifter::start_delay()](crate::LiveShifter::start_delay()).
+    ///
+    /// # Returns
+    ///
+    /// The start delay in samples per channel.
+    pub fn start_delay(&mut self) -> u32 {
+        unsafe {
+            // As in `LiveShifter::start_delay()`, a failed change stays pending for processing
+            let _ = self.process.apply_pending(self.state, &self.params);
+            rubberband_live_get_start_delay(self.state)
+        }
+    }
+
+    /// Process a single block of audio samples, allocating and returning the output.
+    ///
+    /// This is a convenience wrapper around [process_into()](Self::process_into()). As it
+    /// allocates the output, prefer `process_into` on a realtime audio thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if the input channel count or block size is incorrect
+    /// ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    pub fn process<S: Sample>(&mut self, input: &[&[S]]) -> Result<Vec<Vec<S>>, RubberBandError> {
+        let mut output = vec![vec![S::from_f32(0.0); self.block_size]; self.channel_count];
+        let mut output_slices: Vec<&mut [S]> = output
+            .iter_mut()
+            .map(|slice| slice.as_mut_slice())
+            .collect();
+        self.process_into(input, &mut output_slices)?;
+        Ok(output)
+    }
+
+    /// Process a single block of audio samples using pre-allocated output buffers.
+    ///
+    /// Scheduled changes within this block and pending parameter changes from the controllers are
+    /// applied first. This method takes no lock and performs no heap allocation. Samples in formats
+    /// other than `f32` are converted as described in
+    /// [LiveShifter::process_into()](crate::LiveShifter::process_into()).
+    ///
+    /// The input and output buffers must not alias or overlap.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: A slice of slices (`&[&[S]]`) representing the input audio block.
+    ///   - Must have `channel_count` inner slices.
+    ///   - Each inner slice must have `block_size` samples.
+    /// * `output`: A mutable slice of mutable slices (`&mut [&mut [S]]`) for the output.
+    ///   - Must have `channel_count` inner slices.
+    ///   - Each inner slice must have `block_size` samples. The contents will be overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The input/output channel count or block size is incorrect ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn process_into<S: Sample>(&mut self, input: &[&[S]], output: &mut [&mut [S]]) -> Result<(), RubberBandError> {
+        check_block(input, output, self.channel_count, self.block_size)?;
+
+        unsafe {
+            self.process.shift_planar(self.state, &self.params, input, output)
+        }
+    }
+
+    /// Process a single block of audio samples in place, overwriting the input with the output.
+    ///
+    /// This takes no lock and performs no heap allocation.
+    /// See [LiveShifter::process_in_place()](crate::LiveShifter::process_in_place()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The channel count or block size is incorrect ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn process_in_place<S: Sample>(&mut self, buffers: &mut [&mut [S]]) -> Result<(), RubberBandError> {
+        check_channels(buffers, self.channel_count, self.block_size)?;
+
+        unsafe {
+            self.process.shift_in_place(self.state, &self.params, buffers)
+        }
+    }
+
+    /// Process a single interleaved block of audio samples, allocating and returning the output.
+    ///
+    /// See [LiveShifter::process_interleaved()](crate::LiveShifter::process_interleaved()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] in the same cases as
+    /// [process_interleaved_into()](Self::process_interleaved_into()).
+    pub fn process_interleaved<S: Sample>(&mut self, input: &[S]) -> Result<Vec<S>, RubberBandError> {
+        let mut output = vec![S::from_f32(0.0); input.len()];
+        self.process_interleaved_into(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Process a single interleaved block of audio samples using a pre-allocated output buffer.
+    ///
+    /// This takes no lock and performs no heap allocation.
+    /// See [LiveShifter::process_interleaved_into()](crate::LiveShifter::process_interleaved_into()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The input or output length is not `channel_count * block_size` ([`InconsistentBufferLength`](RubberBandError::InconsistentBufferLength)).
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn process_interleaved_into<S: Sample>(&mut self, input: &[S], output: &mut [S]) -> Result<(), RubberBandError> {
+        check_interleaved(input, output, self.channel_count, self.block_size)?;
+
+        unsafe {
+            self.process.shift_interleaved(self.state, &self.params, input, output)
+        }
+    }
+
+    /// Schedule a pitch scale change at the given frame.
+    ///
+    /// See [LiveShifter::schedule_pitch_scale()](crate::LiveShifter::schedule_pitch_scale()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The pitch scale is not a finite positive number ([`InvalidPitchScale`](RubberBandError::InvalidPitchScale)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    pub fn schedule_pitch_scale(&mut self, frame: u64, scale: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_pitch_scale(scale)?;
+        self.params.schedule(frame, ScheduledParam::PitchScale(scale))
+    }
+
+    /// Schedule a formant scale change at the given frame.
+    ///
+    /// See [LiveShifter::schedule_formant_scale()](crate::LiveShifter::schedule_formant_scale()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The formant scale is negative or not finite ([`InvalidFormantScale`](RubberBandError::InvalidFormantScale)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    pub fn schedule_formant_scale(&mut self, frame: u64, scale: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_formant_scale(scale)?;
+        self.params.schedule(frame, ScheduledParam::FormantScale(scale))
+    }
+
+    /// Get the number of frames processed since the last [reset()](Self::reset()).
+    pub fn frame_position(&self) -> u64 {
+        self.process.frame
+    }
+
+    /// Reset the internal state of the processor.
+    ///
+    /// This also sets the frame counter back to 0, discards pending scheduled changes and
+    /// finishes any glide in progress.
+    /// See [LiveShifter::reset()](crate::LiveShifter::reset()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if the C++ library failed to reset.
+    pub fn reset(&mut self) -> Result<(), RubberBandError> {
+        unsafe {
+            self.process.reset(self.state, &self.params)?;
+            check_status(rubberband_shim_live_reset(self.state))
+        }
+    }
+}
+
+impl Drop for LiveShifterProcessor {
+    fn drop(&mut self) {
+        unsafe { rubberband_live_delete(self.state) };
+    }
+}
+
+// The C++ instance is only accessed through `&mut self`.
+unsafe impl Send for LiveShifterProcessor {}
+
+/// A cloneable handle for changing the parameters of a [LiveShifterProcessor] from any thread.
+///
+/// Created by [LiveShifter::split()](crate::LiveShifter::split()) or
+/// [LiveShifterProcessor::controller()]. All methods are lock-free: the values are stored in
+/// atomics shared with the processor, which applies them at the start of its next block. The
+/// controller never touches the C++ instance, so it may outlive the processor.
+///
+/// This type implements `Clone`, `Send` and `Sync`.
+#[derive(Clone)]
+pub struct LiveShifterController {
+    params: Arc<LiveParams>,
+}
+
+impl LiveShifterController {
+    pub(crate) fn new(params: Arc<LiveParams>) -> Self {
+        Self { params }
+    }
+
+    /// Set the pitch scale, applied at the start of the processor's next block.
+    ///
+    /// See [LiveShifter::set_pitch_scale()](crate::LiveShifter::set_pitch_scale()).
+    pub fn set_pitch_scale(&self, scale: f64) {
+        self.params.set_pitch_scale(scale);
+    }
+
+    /// Set the pitch scale, checking that it is valid.
+    ///
+    /// See [LiveShifter::try_set_pitch_scale()](crate::LiveShifter::try_set_pitch_scale()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidPitchScale`](RubberBandError::InvalidPitchScale) if the scale is not a
+    /// finite number greater than 0.
+    pub fn try_set_pitch_scale(&self, scale: f64) -> Result<(), RubberBandError> {
+        check_pitch_scale(scale)?;
+        self.params.set_pitch_scale(scale);
+        Ok(())
+    }
+
+    /// Set the pitch scale, gliding to it over the given time.
+    ///
+    /// See [LiveShifter::set_pitch_scale_with_glide()](crate::LiveShifter::set_pitch_scale_with_glide()).
+    pub fn set_pitch_scale_with_glide(&self, scale: f64, glide: Duration) {
+        self.params.set_pitch_scale_with_glide(scale, glide.as_secs_f64());
+    }
+
+    /// Set the default glide time for pitch scale changes.
+    ///
+    /// See [LiveShifter::set_pitch_glide()](crate::LiveShifter::set_pitch_glide()).
+    pub fn set_pitch_glide(&self, glide: Duration) {
+        self.params.pitch_glide.store(glide.as_secs_f64(), Ordering::Relaxed);
+    }
+
+    /// Get the current and target pitch scale.
+    ///
+    /// See [LiveShifter::pitch_scale()](crate::LiveShifter::pitch_scale()).
+    pub fn pitch_scale(&self) -> GlideState {
+        self.params.pitch_scale()
+    }
+
+    /// Set the pitch shift in semitones, applied at the start of the processor's next block.
+    pub fn set_pitch_semitone(&self, semitones: f64) {
+        self.set_pitch_scale(2.0f64.powf(semitones / 12.0));
+    }
+
+    /// Get the target pitch shift in semitones.
+    pub fn pitch_semitone(&self) -> f64 {
+        12.0 * self.pitch_scale().target.log2()
+    }
+
+    /// Set the pitch shift in cents, applied at the start of the processor's next block.
+    pub fn set_pitch_cent(&self, cents: f64) {
+        self.set_pitch_scale(2.0f64.powf(cents / 1200.0));
+    }
+
+    /// Get the target pitch shift in cents.
+    pub fn pitch_cent(&self) -> f64 {
+        1200.0 * self.pitch_scale().target.log2()
+    }
+
+    /// Set the formant scale, applied at the start of the processor's next block.
+    ///
+    /// See [LiveShifter::set_formant_scale()](crate::LiveShifter::set_formant_scale()).
+    pub fn set_formant_scale(&self, scale: f64) {
+        self.params.set_formant_scale(scale);
+    }
+
+    /// Set the formant scale, checking that it is valid.
+    ///
+    /// See [LiveShifter::try_set_formant_scale()](crate::LiveShifter::try_set_formant_scale()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidFormantScale`](RubberBandError::InvalidFormantScale) if the scale is
+    /// negative or not finite.
+    pub fn try_set_formant_scale(&self, scale: f64) -> Result<(), RubberBandError> {
+        check_formant_scale(scale)?;
+        self.params.set_formant_scale(scale);
+        Ok(())
+    }
+
+    /// Set the formant scale, gliding to it over the given time.
+    ///
+    /// See [LiveShifter::set_formant_scale_with_glide()](crate::LiveShifter::set_formant_scale_with_glide()).
+    pub fn set_formant_scale_with_glide(&self, scale: f64, glide: Duration) {
+        self.params.set_formant_scale_with_glide(scale, glide.as_secs_f64());
+    }
+
+    /// Set the default glide time for formant scale changes.
+    ///
+    /// See [LiveShifter::set_formant_glide()](crate::LiveShifter::set_formant_glide()).
+    pub fn set_formant_glide(&self, glide: Duration) {
+        self.params.formant_glide.store(glide.as_secs_f64(), Ordering::Relaxed);
+    }
+
+    /// Get the current and target formant scale, `0.0` meaning automatic.
+    pub fn formant_scale(&self) -> GlideState {
+        self.params.formant_scale()
+    }
+
+    /// Set the curve of pitch and formant scale glides.
+    ///
+    /// See [LiveShifter::set_glide_curve()](crate::LiveShifter::set_glide_curve()).
+    pub fn set_glide_curve(&self, curve: GlideCurve) {
+        self.params.set_glide_curve(curve);
+    }
+
+    /// Set the formant preservation option, applied at the start of the processor's next block.
+    ///
+    /// See [LiveShifter::set_formant_option()](crate::LiveShifter::set_formant_option()).
+    pub fn set_formant_option(&self, option: LiveShifterFormant) {
+        self.params.set_formant_option(option);
+    }
+
+    /// Schedule a pitch scale change at the given frame of the processor.
+    ///
+    /// See [LiveShifter::schedule_pitch_scale()](crate::LiveShifter::schedule_pitch_scale()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The pitch scale is not a finite positive number ([`InvalidPitchScale`](RubberBandError::InvalidPitchScale)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    pub fn schedule_pitch_scale(&self, frame: u64, scale: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_pitch_scale(scale)?;
+        self.params.schedule(frame, ScheduledParam::PitchScale(scale))
+    }
+
+    /// Schedule a formant scale change at the given frame of the processor.
+    ///
+    /// See [LiveShifter::schedule_formant_scale()](crate::LiveShifter::schedule_formant_scale()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The formant scale is negative or not finite ([`InvalidFormantScale`](RubberBandError::InvalidFormantScale)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    pub fn schedule_formant_scale(&self, frame: u64, scale: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_formant_scale(scale)?;
+        self.params.schedule(frame, ScheduledParam::FormantScale(scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LiveShifterBuilder;
+
+    #[test]
+    fn test_processor_matches_shifter() {
+        let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        shifter.set_pitch_scale(1.5);
+        let block_size = shifter.block_size() as usize;
+
+        let (mut processor, _) = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap().split();
+        processor.controller().set_pitch_scale(1.5);
+        assert_eq!(processor.block_size() as usize, block_size);
+        assert_eq!(processor.channel_count(), 1);
+        assert_eq!(processor.sample_rate(), 44100);
+
+        let input: Vec<f32> = (0..block_size * 10).map(|n| (n as f32 * 0.02).sin()).collect();
+        for block in input.chunks(block_size) {
+            let expected = shifter.process(&[block]).unwrap();
+            let output = processor.process(&[block]).unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_split_carries_settings() {
+        let shifter = LiveShifterBuilder::new(48000, 1).unwrap().build().unwrap();
+        shifter.set_pitch_scale(2.0);
+        let expected_delay = shifter.start_delay();
+        shifter.set_formant_scale(1.25);
+
+        let (mut processor, controller) = shifter.split();
+        assert_eq!(controller.pitch_scale().target, 2.0);
+        assert_eq!(controller.formant_scale().target, 1.25);
+        assert_eq!(processor.start_delay(), expected_delay);
+    }
+
+    #[test]
+    fn test_controller_changes_pitch() {
+        let (mut processor, controller) = LiveShifterBuilder::new(48000, 1).unwrap().build().unwrap().split();
+        let initial_delay = processor.start_delay();
+        let block_size = processor.block_size() as usize;
+        let input = vec![0.0f32; block_size];
+
+        // Clones share the same parameters
+        let other = controller.clone();
+        other.set_pitch_semitone(12.0);
+        assert_eq!(controller.pitch_scale().target, 2.0);
+        processor.process(&[&input]).unwrap();
+        assert_ne!(processor.start_delay(), initial_delay);
+
+        controller.set_pitch_cent(0.0);
+        processor.process(&[&input]).unwrap();
+        assert_eq!(processor.start_delay(), initial_delay);
+    }
+
+    #[test]
+    fn test_controller_outlives_processor() {
+        let (processor, controller) = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap().split();
+        drop(processor);
+        controller.set_pitch_scale(0.5);
+        controller.set_formant_option(LiveShifterFormant::Preserved);
+        assert_eq!(controller.pitch_scale().target, 0.5);
+    }
+
+    #[test]
+    fn test_processor_schedule() {
+        let (mut processor, controller) = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap().split();
+        let block_size = processor.block_size() as u64;
+        let input = vec![0.0f32; block_size as usize];
+
+        let change = processor.schedule_pitch_scale(block_size * 2 + 1, 0.5).unwrap();
+        assert_eq!(change.applied, block_size * 2);
+
+        for expected in [1.0, 1.0, 0.5] {
+            processor.process(&[&input]).unwrap();
+            assert_eq!(controller.pitch_scale().target, expected);
+        }
+        assert_eq!(processor.frame_position(), block_size * 3);
+
+        processor.schedule_formant_scale(block_size * 4, 2.0).unwrap();
+        processor.reset().unwrap();
+        assert_eq!(processor.frame_position(), 0);
+        for _ in 0..5 {
+            processor.process(&[&input]).unwrap();
+        }
+        assert_eq!(controller.formant_scale().target, 0.0);
+    }
+
+    #[test]
+    fn test_controller_schedule() {
+        let (mut processor, controller) = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap().split();
+        let block_size = processor.block_size() as u64;
+        let input = vec![0.0f32; block_size as usize];
+
+        processor.process(&[&input]).unwrap();
+        let change = std::thread::scope(|scope| {
+            scope.spawn(|| controller.schedule_pitch_scale(block_size * 2 + 1, 0.5)).join().unwrap()
+        })
+        .unwrap();
+        assert_eq!(change.applied, block_size * 2);
+        // A change in a block already processed is applied at the next one
+        let change = controller.schedule_formant_scale(0, 2.0).unwrap();
+        assert_eq!(change.applied, block_size);
+
+        processor.process(&[&input]).unwrap();
+        assert_eq!(controller.pitch_scale().target, 1.0);
+        assert_eq!(controller.formant_scale().target, 2.0);
+        processor.process(&[&input]).unwrap();
+        assert_eq!(controller.pitch_scale().target, 0.5);
+    }
+
+    #[test]
+    fn test_processor_invalid_block() {
+        let (mut processor, _) = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap().split();
+        let block_size = processor.block_size() as usize;
+
+        let input = vec![0.0f32; block_size];
+        assert!(matches!(
+            processor.process(&[&input]),
+            Err(RubberBandError::InconsistentChannelCount { expected: 2, actual: 1 })
+        ));
+        assert!(matches!(
+            processor.process(&[&input, &input[1..]]),
+            Err(RubberBandError::InconsistentBlockSize { channel: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,418 @@
+use parking_lot::Mutex;
+
+use crate::{LiveShifter, RubberBandError};
+
+/// An adapter that lets a [LiveShifter] process blocks of any size.
+///
+/// [LiveShifter::process_into()] only accepts blocks of exactly
+/// [block_size()](LiveShifter::block_size()) samples, while audio hosts usually call back with
+/// their own buffer size, which may be smaller or even vary between calls. This adapter keeps an
+/// input and an output FIFO of one block each: every call to [process_into()](Self::process_into())
+/// takes any number of samples and returns the same number, running the shifter whenever a full
+/// block has been collected.
+///
+/// The FIFOs add a fixed [latency()](Self::latency()) of `block_size() - 1` samples on top of the
+/// shifter's [start delay](LiveShifter::start_delay()), regardless of the host buffer size. Use
+/// [total_latency()](Self::total_latency()) to get both combined.
+///
+/// # Thread Safety
+///
+/// Like [LiveShifter], the stream is `Send + Sync` and its processing methods take `&self`, so it
+/// can be shared (e.g. via `Arc`) between the audio thread and a control thread. The pitch and
+/// formant setters of the wrapped shifter, reached through [shifter()](Self::shifter()), can be
+/// called concurrently with processing. Concurrent calls to [process_into()](Self::process_into())
+/// return [RubberBandError::OperationInProgress] instead of blocking.
+///
+/// # Realtime Safety
+///
+/// All buffers are allocated in [new()](Self::new()). [process_into()](Self::process_into())
+/// performs no allocation.
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{LiveShifterBuilder, LiveShifterStream};
+///
+/// let shifter = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+/// let stream = LiveShifterStream::new(shifter);
+/// stream.shifter().set_pitch_semitone(3.0);
+///
+/// // The host calls back with 128 samples per channel
+/// let input_ch1 = vec![0.1f32; 128];
+/// let input_ch2 = vec![-0.1f32; 128];
+/// let mut output_ch1 = vec![0.0f32; 128];
+/// let mut output_ch2 = vec![0.0f32; 128];
+///
+/// stream
+///     .process_into(&[&input_ch1, &input_ch2], &mut [&mut output_ch1, &mut output_ch2])
+///     .unwrap();
+///
+/// // The output lags the input by this many samples
+/// let latency = stream.total_latency();
+/// ```
+pub struct LiveShifterStream {
+    shifter: LiveShifter,
+    channel_count: usize,
+    block_size: usize,
+    fifo: Mutex<Fifo>,
+}
+
+/// The FIFO state, guarded by the stream's lock.
+struct Fifo {
+    input: Vec<Vec<f32>>,
+    output: Vec<Vec<f32>>,
+    /// Pointers into `input`, passed to the shifter. Taken again before each block is shifted,
+    /// as writing through `input` and `output` invalidates earlier pointers.
+    input_ptrs: Vec<*const f32>,
+    /// Pointers into `output`, passed to the shifter.
+    output_ptrs: Vec<*mut f32>,
+    /// The number of samples collected in the input block. The next output sample is always read
+    /// from the position just after it.
+    fill: usize,
+}
+
+impl Fifo {
+    fn clear(&mut self) {
+        for block in self.input.iter_mut().chain(self.output.iter_mut()) {
+            block.fill(0.0);
+        }
+        self.fill = 0;
+    }
+}
+
+impl LiveShifterStream {
+    /// Create a new [LiveShifterStream] wrapping the given shifter.
+    ///
+    /// The FIFOs are allocated here, sized from the shifter's channel count and block size.
+    ///
+    /// # Arguments
+    ///
+    /// * `shifter`: The [LiveShifter] to wrap.
+    pub fn new(shifter: LiveShifter) -> Self {
+        let channel_count = shifter.channel_count() as usize;
+        let block_size = shifter.block_size() as usize;
+
+        Self {
+            shifter,
+            channel_count,
+            block_size,
+            fifo: Mutex::new(Fifo {
+                input: vec![vec![0.0f32; block_size]; channel_count],
+                output: vec![vec![0.0f32; block_size]; channel_count],
+                input_ptrs: vec![std::ptr::null(); channel_count],
+                output_ptrs: vec![std::ptr::null_mut(); channel_count],
+                fill: 0,
+            }),
+        }
+    }
+
+    /// Get the wrapped [LiveShifter], e.g. to change the pitch scale.
+    pub fn shifter(&self) -> &LiveShifter {
+        &self.shifter
+    }
+
+    /// Consume the stream and return the wrapped [LiveShifter].
+    pub fn into_inner(self) -> LiveShifter {
+        self.shifter
+    }
+
+    /// Get the latency (in samples per channel) added by the FIFOs.
+    ///
+    /// This is `block_size() - 1` and does not depend on the host buffer size. It does **not**
+    /// include the shifter's own [start delay](LiveShifter::start_delay()).
+    ///
+    /// # Returns
+    ///
+    /// The FIFO latency in samples per channel.
+    pub fn latency(&self) -> u32 {
+        self.block_size as u32 - 1
+    }
+
+    /// Get the total latency (in samples per channel) between the input and the output.
+    ///
+    /// This is [latency()](Self::latency()) plus the shifter's
+    /// [start delay](LiveShifter::start_delay()), which depends on the pitch scale.
+    ///
+    /// **Note:** Like [LiveShifter::start_delay()], this acquires the shifter's processing lock.
+    ///
+    /// # Returns
+    ///
+    /// The total latency in samples per channel.
+    pub fn total_latency(&self) -> u32 {
+        self.latency() + self.shifter.start_delay()
+    }
+
+    /// Process any number of samples, writing the same number of samples to the output.
+    ///
+    /// If this returns an error for invalid buffers, a concurrent call, or pending parameter
+    /// changes the C++ library fails to apply, it does so before touching the FIFOs, so the
+    /// stream is left as it was and the call can be repeated. If the C++ library fails while
+    /// shifting a block, that block is lost: the FIFOs are cleared, the output is silent from the
+    /// first sample of this call that went into the block, and the rest of the input is dropped.
+    /// The next call carries on at the same [latency](Self::latency()), and [reset()](Self::reset())
+    /// clears the state of the wrapped shifter as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: A slice of slices (`&[&[f32]]`) representing the input audio.
+    ///   - Must have `channel_count` inner slices, all of the same length.
+    /// * `output`: A mutable slice of mutable slices (`&mut [&mut [f32]]`) for the output.
+    ///   - Must have `channel_count` inner slices, each as long as the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - Input/output channel count is incorrect, or the channels differ in length ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - A concurrent call to `process_into` or `reset` is in progress on the same stream, or the
+    ///   wrapped shifter is busy ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    /// - The C++ library failed to apply a parameter change or to shift a block
+    ///   ([`OutOfMemory`](RubberBandError::OutOfMemory), [`InvalidArgument`](RubberBandError::InvalidArgument),
+    ///   [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn process_into(&self, input: &[&[f32]], output: &mut [&mut [f32]]) -> Result<(), RubberBandError> {
+        let mut fifo = self.fifo.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        if input.len() != self.channel_count {
+            return Err(RubberBandError::InconsistentChannelCount {
+                expected: self.channel_count,
+                actual: input.len(),
+            });
+        }
+        if output.len() != self.channel_count {
+            return Err(RubberBandError::InconsistentChannelCount {
+                expected: self.channel_count,
+                actual: output.len(),
+            });
+        }
+        let length = input[0].len();
+        for ch in 0..self.channel_count {
+            if input[ch].len() != length {
+                return Err(RubberBandError::InconsistentBlockSize {
+                    channel: ch,
+                    expected: length,
+                    actual: input[ch].len(),
+                });
+            }
+            if output[ch].len() != length {
+                return Err(RubberBandError::InconsistentBlockSize {
+                    channel: ch,
+                    expected: length,
+                    actual: output[ch].len(),
+                });
+            }
+        }
+
+        let block_size = self.block_size;
+        let fifo = &mut *fifo;
+        // Lock the shifter and apply its pending changes before touching the FIFOs, so that these
+        // errors leave the stream as it was
+        let mut process = if fifo.fill + length >= block_size {
+            Some(self.shifter.lock_raw()?)
+        } else {
+            None
+        };
+
+        let mut done = 0;
+        while done < length {
+            let fill = fifo.fill;
+            let chunk = (length - done).min(block_size - fill);
+
+            for (block, slice) in fifo.input.iter_mut().zip(input) {
+                block[fill..fill + chunk].copy_from_slice(&slice[done..done + chunk]);
+            }
+
+            if fill + chunk == block_size {
+                // All but the last sample come from the current output block, the last one from
+                // the block about to be processed
+                for (slice, block) in output.iter_mut().zip(&fifo.output) {
+                    slice[done..done + chunk - 1].copy_from_slice(&block[fill + 1..]);
+                }
+                for (ptr, block) in fifo.input_ptrs.iter_mut().zip(&fifo.input) {
+                    *ptr = block.as_ptr();
+                }
+                for (ptr, block) in fifo.output_ptrs.iter_mut().zip(&mut fifo.output) {
+                    *ptr = block.as_mut_ptr();
+                }
+                let process = process.as_deref_mut().expect("the shifter is locked when a block is completed");
+                let shifted = unsafe {
+                    self.shifter.shift_raw(process, fifo.input_ptrs.as_ptr(), fifo.output_ptrs.as_ptr())
+                };
+                if let Err(err) = shifted {
+                    // The block is lost, and the output block may be partly written
+                    fifo.clear();
+                    for slice in output.iter_mut() {
+                        slice[done..].fill(0.0);
+                    }
+                    return Err(err);
+                }
+                for (slice, block) in output.iter_mut().zip(&fifo.output) {
+                    slice[done + chunk - 1] = block[0];
+                }
+                fifo.fill = 0;
+            } else {
+                for (slice, block) in output.iter_mut().zip(&fifo.output) {
+                    slice[done..done + chunk].copy_from_slice(&block[fill + 1..fill + 1 + chunk]);
+                }
+                fifo.fill = fill + chunk;
+            }
+            done += chunk;
+        }
+
+        Ok(())
+    }
+
+    /// Reset the stream and the wrapped shifter.
+    ///
+    /// This clears the FIFOs and the shifter's internal state, retaining all parameter settings.
+    ///
+    /// **Note:** This method acquires the stream's lock and then the shifter's processing lock.
+    /// Calling it concurrently with [process_into()](Self::process_into()) will block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if the C++ library failed to reset the shifter.
+    pub fn reset(&self) -> Result<(), RubberBandError> {
+        let mut fifo = self.fifo.lock();
+        fifo.clear();
+        self.shifter.reset()
+    }
+}
+
+// The raw pointers in `Fifo` only point into the FIFO's own heap buffers, which move with it and
+// are only accessed while holding the lock.
+unsafe impl Send for LiveShifterStream {}
+unsafe impl Sync for LiveShifterStream {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LiveShifterBuilder;
+
+    /// Process `input` through a fresh shifter, block by block.
+    fn process_blocks(input: &[f32], block_size: usize) -> Vec<f32> {
+        let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        shifter.set_pitch_scale(1.2);
+        input
+            .chunks(block_size)
+            .flat_map(|block| shifter.process(&[block]).unwrap().remove(0))
+            .collect()
+    }
+
+    /// Process `input` through a fresh stream, with the given host buffer sizes in turn.
+    fn process_stream(input: &[f32], sizes: &[usize]) -> Vec<f32> {
+        let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        shifter.set_pitch_scale(1.2);
+        let stream = LiveShifterStream::new(shifter);
+
+        let mut output = vec![0.0f32; input.len()];
+        let mut pos = 0;
+        for &size in sizes.iter().cycle() {
+            if pos == input.len() {
+                break;
+            }
+            let end = (pos + size).min(input.len());
+            stream.process_into(&[&input[pos..end]], &mut [&mut output[pos..end]]).unwrap();
+            pos = end;
+        }
+        output
+    }
+
+    #[test]
+    fn test_stream_matches_block_processing() {
+        let block_size = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap().block_size() as usize;
+        let input: Vec<f32> = (0..block_size * 20).map(|n| (n as f32 * 0.03).sin()).collect();
+        let expected = process_blocks(&input, block_size);
+
+        // Whatever the host buffer sizes, the output is the block output delayed by the latency
+        let latency = block_size - 1;
+        for sizes in [&[64][..], &[128], &[100], &[1, 7, 300, 1024, 33], &[block_size]] {
+            let output = process_stream(&input, sizes);
+            assert!(output[..latency].iter().all(|x| *x == 0.0));
+            assert_eq!(&output[latency..], &expected[..input.len() - latency], "Mismatch for sizes {:?}", sizes);
+        }
+    }
+
+    #[test]
+    fn test_stream_busy_shifter() {
+        let block_size = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap().block_size() as usize;
+        let input: Vec<f32> = (0..block_size * 4).map(|n| (n as f32 * 0.03).sin()).collect();
+        let expected = process_stream(&input, &[100]);
+
+        let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        shifter.set_pitch_scale(1.2);
+        let stream = LiveShifterStream::new(shifter);
+
+        // Calls that need the busy shifter fail without touching the FIFOs or the output, and
+        // succeed when repeated
+        let mut output = vec![0.0f32; input.len()];
+        let mut failures = 0;
+        for pos in (0..input.len()).step_by(100) {
+            let end = (pos + 100).min(input.len());
+            {
+                let _busy = stream.shifter().mutex.lock();
+                let mut attempt = vec![7.0f32; end - pos];
+                match stream.process_into(&[&input[pos..end]], &mut [&mut attempt]) {
+                    Ok(()) => {
+                        output[pos..end].copy_from_slice(&attempt);
+                        continue;
+                    }
+                    Err(err) => {
+                        assert!(matches!(err, RubberBandError::OperationInProgress));
+                        assert!(attempt.iter().all(|x| *x == 7.0));
+                        failures += 1;
+                    }
+                }
+            }
+            stream.process_into(&[&input[pos..end]], &mut [&mut output[pos..end]]).unwrap();
+        }
+        assert!(failures > 0);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_stream_latency() {
+        let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        let block_size = shifter.block_size();
+        let start_delay = shifter.start_delay();
+
+        let stream = LiveShifterStream::new(shifter);
+        assert_eq!(stream.latency(), block_size - 1);
+        assert_eq!(stream.total_latency(), block_size - 1 + start_delay);
+    }
+
+    #[test]
+    fn test_stream_invalid_input() {
+        let shifter = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+        let stream = LiveShifterStream::new(shifter);
+
+        let input = [vec![0.0f32; 64], vec![0.0f32; 64]];
+        let mut output = [vec![0.0f32; 64], vec![0.0f32; 63]];
+        let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
+
+        let mut output_slices: Vec<&mut [f32]> = output.iter_mut().map(|v| v.as_mut_slice()).collect();
+        assert!(matches!(
+            stream.process_into(&input_slices[..1], &mut output_slices),
+            Err(RubberBandError::InconsistentChannelCount { expected: 2, actual: 1 })
+        ));
+        assert!(matches!(
+            stream.process_into(&input_slices, &mut output_slices),
+            Err(RubberBandError::InconsistentBlockSize { channel: 1, expected: 64, actual: 63 })
+        ));
+    }
+
+    #[test]
+    fn test_stream_reset() {
+        let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+        let stream = LiveShifterStream::new(shifter);
+        let block_size = stream.shifter().block_size() as usize;
+
+        let input = vec![0.5f32; block_size * 20];
+        let mut output = vec![0.0f32; block_size * 20];
+        stream.process_into(&[&input], &mut [&mut output]).unwrap();
+        assert!(!output.iter().all(|x| *x == 0.0));
+
+        // After reset, the FIFOs and the shifter are cleared and the output starts silent again
+        stream.reset().unwrap();
+        stream.process_into(&[&input[..block_size]], &mut [&mut output[..block_size]]).unwrap();
+        assert!(output[..block_size].iter().all(|x| *x == 0.0));
+    }
+}
@@ -0,0 +1,212 @@
+/// An audio sample format that can be processed by [LiveShifter](crate::LiveShifter).
+///
+/// Samples are converted to and from `f32` in the range `-1.0..1.0`, which the C++ library
+/// processes internally. Implementations are provided for `f32`, `f64`, `i16`, [I24] and `u8`.
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{I24, Sample};
+///
+/// assert_eq!(i16::MIN.to_f32(), -1.0);
+/// assert_eq!(i16::from_f32(0.5), 16384);
+/// assert_eq!(u8::from_f32(0.0), 128);
+/// assert_eq!(I24::from_f32(2.0), I24::MAX);
+/// ```
+pub trait Sample: Copy + Send + Sync + 'static {
+    /// The size of one quantization step in the range `-1.0..1.0`, i.e. the value of the least
+    /// significant bit, used to scale the dither noise. This is `0.0` for floating-point formats,
+    /// which are never dithered.
+    const LSB: f32;
+
+    /// Convert the sample to `f32`, scaling integer formats to the range `-1.0..1.0`.
+    fn to_f32(self) -> f32;
+
+    /// Convert an `f32` sample to this format, scaling to the full range of integer formats with
+    /// rounding and clipping.
+    fn from_f32(value: f32) -> Self;
+}
+
+impl Sample for f32 {
+    const LSB: f32 = 0.0;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+impl Sample for f64 {
+    const LSB: f32 = 0.0;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+}
+
+impl Sample for i16 {
+    const LSB: f32 = 1.0 / 32768.0;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / 32768.0
+    }
+
+    fn from_f32(value: f32) -> Self {
+        // `as` saturates, clipping values outside the range
+        (value * 32768.0).round() as i16
+    }
+}
+
+impl Sample for u8 {
+    const LSB: f32 = 1.0 / 128.0;
+
+    fn to_f32(self) -> f32 {
+        (self as f32 - 128.0) / 128.0
+    }
+
+    fn from_f32(value: f32) -> Self {
+        // Convert to signed first, so that NaN maps to silence (128) rather than full scale
+        ((value * 128.0).round() as i8 as i16 + 128) as u8
+    }
+}
+
+/// A 24-bit signed sample stored in the low bits of an `i32`, as used by 24-bit PCM APIs and files.
+///
+/// Values are kept within [I24::MIN] and [I24::MAX] when converting from `f32`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct I24(pub i32);
+
+impl I24 {
+    /// The smallest 24-bit sample value.
+    pub const MIN: I24 = I24(-(1 << 23));
+
+    /// The largest 24-bit sample value.
+    pub const MAX: I24 = I24((1 << 23) - 1);
+}
+
+impl Sample for I24 {
+    const LSB: f32 = 1.0 / 8388608.0;
+
+    fn to_f32(self) -> f32 {
+        self.0 as f32 / 8388608.0
+    }
+
+    fn from_f32(value: f32) -> Self {
+        I24(((value as f64 * 8388608.0).round() as i32).clamp(Self::MIN.0, Self::MAX.0))
+    }
+}
+
+/// Dithering options for integer output formats, set with
+/// [LiveShifterBuilder::dither()](crate::LiveShifterBuilder::dither()).
+///
+/// Dither only applies when narrowing to an integer [Sample] format; floating-point output is
+/// never dithered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Round to the nearest integer value. This is the default option.
+    None,
+    /// Add triangular (TPDF) noise of ±1 LSB before rounding, which decorrelates the quantization
+    /// error from the signal.
+    Triangular,
+}
+
+/// A realtime-safe source of TPDF dither noise.
+pub(crate) struct DitherNoise {
+    /// The xorshift32 generator state, which must not be 0.
+    state: u32,
+}
+
+impl DitherNoise {
+    pub(crate) fn new() -> Self {
+        Self { state: 0x9E37_79B9 }
+    }
+
+    /// Get a uniformly distributed value in `0.0..1.0`.
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Get a triangularly distributed value in `-1.0..1.0`.
+    fn triangular(&mut self) -> f32 {
+        self.uniform() - self.uniform()
+    }
+}
+
+/// Convert an `f32` sample to `S`, adding dither noise for integer formats if enabled.
+pub(crate) fn quantize<S: Sample>(value: f32, noise: &mut Option<DitherNoise>) -> S {
+    match noise {
+        Some(noise) if S::LSB > 0.0 => S::from_f32(value + noise.triangular() * S::LSB),
+        _ => S::from_f32(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_scaling() {
+        assert_eq!(i16::from_f32(-1.0), i16::MIN);
+        assert_eq!(i16::from_f32(1.0), i16::MAX);
+        assert_eq!(i16::from_f32(0.0), 0);
+        assert_eq!(16384i16.to_f32(), 0.5);
+
+        assert_eq!(u8::from_f32(-1.0), 0);
+        assert_eq!(u8::from_f32(1.0), u8::MAX);
+        assert_eq!(0u8.to_f32(), -1.0);
+        assert_eq!(192u8.to_f32(), 0.5);
+
+        assert_eq!(I24::from_f32(-1.0), I24::MIN);
+        assert_eq!(I24::from_f32(1.0), I24::MAX);
+        assert_eq!(I24(1 << 22).to_f32(), 0.5);
+        assert_eq!(I24::from_f32(-3.0), I24::MIN);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for value in [i16::MIN, -1234, 0, 1, i16::MAX] {
+            assert_eq!(i16::from_f32(value.to_f32()), value);
+        }
+        for value in [0u8, 1, 127, 128, 255] {
+            assert_eq!(u8::from_f32(value.to_f32()), value);
+        }
+        for value in [I24::MIN, I24(-5), I24(0), I24(12345), I24::MAX] {
+            assert_eq!(I24::from_f32(value.to_f32()), value);
+        }
+        assert_eq!(f64::from_f32(0.25), 0.25);
+    }
+
+    #[test]
+    fn test_nan_is_silence() {
+        assert_eq!(i16::from_f32(f32::NAN), 0);
+        assert_eq!(I24::from_f32(f32::NAN), I24(0));
+        assert_eq!(u8::from_f32(f32::NAN), 128);
+    }
+
+    #[test]
+    fn test_dither() {
+        let mut noise = Some(DitherNoise::new());
+
+        // Dither noise stays within ±1 LSB, and averages out
+        let samples: Vec<i16> = (0..10000).map(|_| quantize(0.25, &mut noise)).collect();
+        assert!(samples.iter().all(|&s| (8191..=8193).contains(&s)));
+        let mean = samples.iter().map(|&s| s as f64).sum::<f64>() / samples.len() as f64;
+        assert!((mean - 8192.0).abs() < 0.05, "Dither is biased: mean {}", mean);
+        assert!(samples.iter().any(|&s| s != 8192));
+
+        // Floating-point formats and disabled dither are not affected
+        assert_eq!(quantize::<f64>(0.25, &mut noise), 0.25);
+        assert_eq!(quantize::<i16>(0.25, &mut None), 8192);
+    }
+}
@@ -0,0 +1,319 @@
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::RubberBandError;
+
+/// Where a scheduled parameter change takes effect, as returned by the `schedule_*` methods (e.g.
+/// [LiveShifter::schedule_pitch_scale()](crate::LiveShifter::schedule_pitch_scale())).
+///
+/// A [LiveShifter](crate::LiveShifter) applies changes at block boundaries: a change takes effect
+/// from the first frame of the block containing the requested frame, or from the next block if
+/// that one has already been processed. A [Stretcher](crate::Stretcher) splits its processing at
+/// the requested frame instead, so a change only moves if that frame has already been processed.
+/// Frames are counted from the last reset.
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::LiveShifterBuilder;
+///
+/// let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+/// let block_size = shifter.block_size() as u64;
+///
+/// let change = shifter.schedule_pitch_scale(block_size + 10, 1.5).unwrap();
+/// assert_eq!(change.requested, block_size + 10);
+/// assert_eq!(change.applied, block_size);
+/// assert_eq!(change.quantization(), -10);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledChange {
+    /// The frame at which the change was requested.
+    pub requested: u64,
+    /// The frame at which the change takes effect.
+    pub applied: u64,
+}
+
+impl ScheduledChange {
+    /// Get how far the change was moved by the block quantization, in frames.
+    ///
+    /// A negative value means the change takes effect early, a positive value that it takes
+    /// effect late (because its block had already been processed when it was scheduled).
+    pub fn quantization(&self) -> i64 {
+        self.applied as i64 - self.requested as i64
+    }
+}
+
+/// A [LiveShifter](crate::LiveShifter) parameter value to be applied at a scheduled frame.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ScheduledParam {
+    PitchScale(f64),
+    FormantScale(f64),
+}
+
+/// The lock-free inbox through which parameter changes are scheduled from any thread.
+///
+/// Changes are pushed here by the `schedule_*` methods and moved into the processing thread's
+/// [Schedule] at the start of each block, so scheduling never waits for (or fails because of) a
+/// processing call in progress. The inbox is a bounded multi-producer queue with a sequence number
+/// per slot; its storage is allocated up front, so pushing and receiving never allocate.
+pub(crate) struct ScheduleQueue<P> {
+    slots: Box<[Slot<(u64, P)>]>,
+    /// The position of the next slot to push to.
+    tail: AtomicUsize,
+    /// The position of the next slot to receive from.
+    head: AtomicUsize,
+    /// The number of changes pushed and not yet applied or discarded, in the inbox or in the
+    /// [Schedule], which together never hold more than the capacity.
+    pending: AtomicUsize,
+    /// The frame processed up to, published by the processing thread.
+    frame: AtomicU64,
+}
+
+struct Slot<T> {
+    /// The position of the value in the slot when it is full, or the position it can be pushed
+    /// at when it is empty.
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// The slots are only accessed by the thread that claimed them through the sequence numbers.
+unsafe impl<P: Send> Send for ScheduleQueue<P> {}
+unsafe impl<P: Send> Sync for ScheduleQueue<P> {}
+
+impl<P: Copy> ScheduleQueue<P> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity)
+                .map(|position| Slot {
+                    sequence: AtomicUsize::new(position),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+                .collect(),
+            tail: AtomicUsize::new(0),
+            head: AtomicUsize::new(0),
+            pending: AtomicUsize::new(0),
+            frame: AtomicU64::new(0),
+        }
+    }
+
+    /// Schedule a change at the given frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScheduleFull`](RubberBandError::ScheduleFull) if as many changes as the capacity
+    /// are already pending.
+    pub(crate) fn push(&self, frame: u64, param: P) -> Result<(), RubberBandError> {
+        let capacity = self.slots.len();
+        self.pending
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| (pending < capacity).then_some(pending + 1))
+            .map_err(|_| RubberBandError::ScheduleFull(capacity))?;
+
+        // The reservation above leaves a free slot, which may still be in the middle of being
+        // received by the processing thread.
+        let mut position = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position % capacity];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            if sequence == position {
+                match self.tail.compare_exchange_weak(position, position.wrapping_add(1), Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write((frame, param)) };
+                        slot.sequence.store(position.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => position = current,
+                }
+            } else if (sequence.wrapping_sub(position) as isize) < 0 {
+                std::hint::spin_loop();
+                position = self.tail.load(Ordering::Relaxed);
+            } else {
+                position = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Take the oldest change from the inbox, if any.
+    fn pop(&self) -> Option<(u64, P)> {
+        let capacity = self.slots.len();
+        if capacity == 0 {
+            return None;
+        }
+        let mut position = self.head.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position % capacity];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let filled = position.wrapping_add(1);
+            if sequence == filled {
+                match self.head.compare_exchange_weak(position, filled, Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init() };
+                        slot.sequence.store(position.wrapping_add(capacity), Ordering::Release);
+                        return Some(value);
+                    }
+                    Err(current) => position = current,
+                }
+            } else if (sequence.wrapping_sub(filled) as isize) < 0 {
+                return None;
+            } else {
+                position = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Release the reservations of `count` changes that were applied or discarded.
+    fn release(&self, count: usize) {
+        if count > 0 {
+            self.pending.fetch_sub(count, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the frame processed up to, as last published by the processing thread.
+    pub(crate) fn frame(&self) -> u64 {
+        self.frame.load(Ordering::Acquire)
+    }
+
+    /// Publish the frame processed up to.
+    pub(crate) fn set_frame(&self, frame: u64) {
+        self.frame.store(frame, Ordering::Release);
+    }
+}
+
+/// The parameter changes received by the processing thread, ordered by frame.
+///
+/// The storage is allocated up front, so receiving and draining never allocate.
+pub(crate) struct Schedule<P> {
+    /// Pending changes, sorted by frame, and by scheduling order within a frame.
+    events: Vec<(u64, P)>,
+}
+
+impl<P: Copy> Schedule<P> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Move the changes pushed to `queue` since the last call into the schedule.
+    pub(crate) fn receive(&mut self, queue: &ScheduleQueue<P>) {
+        while let Some((frame, param)) = queue.pop() {
+            // The capacity is reserved by the push, so this does not allocate
+            let index = self.events.partition_point(|(f, _)| *f <= frame);
+            self.events.insert(index, (frame, param));
+        }
+    }
+
+    /// Remove the changes before the given frame, passing them to `apply` in order.
+    pub(crate) fn drain_before<F>(&mut self, queue: &ScheduleQueue<P>, end: u64, apply: F)
+    where F: FnMut(P)
+    {
+        let count = self.events.partition_point(|(f, _)| *f < end);
+        self.events.drain(..count).map(|(_, param)| param).for_each(apply);
+        queue.release(count);
+    }
+
+    /// Get the frame of the earliest pending change, if any.
+    pub(crate) fn next_frame(&self) -> Option<u64> {
+        self.events.first().map(|(frame, _)| *frame)
+    }
+
+    /// Discard the pending changes, including those still in `queue`.
+    pub(crate) fn clear(&mut self, queue: &ScheduleQueue<P>) {
+        self.receive(queue);
+        queue.release(self.events.len());
+        self.events.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pitch(param: ScheduledParam) -> f64 {
+        match param {
+            ScheduledParam::PitchScale(scale) => scale,
+            ScheduledParam::FormantScale(_) => panic!("Unexpected formant scale"),
+        }
+    }
+
+    #[test]
+    fn test_schedule_order() {
+        let queue = ScheduleQueue::with_capacity(4);
+        let mut schedule = Schedule::with_capacity(4);
+        queue.push(300, ScheduledParam::PitchScale(3.0)).unwrap();
+        queue.push(100, ScheduledParam::PitchScale(1.0)).unwrap();
+        queue.push(200, ScheduledParam::PitchScale(2.0)).unwrap();
+        queue.push(100, ScheduledParam::PitchScale(1.5)).unwrap();
+        assert_eq!(schedule.next_frame(), None);
+        schedule.receive(&queue);
+
+        let mut applied = Vec::new();
+        schedule.drain_before(&queue, 200, |param| applied.push(pitch(param)));
+        assert_eq!(applied, vec![1.0, 1.5]);
+        assert_eq!(schedule.next_frame(), Some(200));
+
+        applied.clear();
+        schedule.drain_before(&queue, 1000, |param| applied.push(pitch(param)));
+        assert_eq!(applied, vec![2.0, 3.0]);
+        assert_eq!(schedule.next_frame(), None);
+    }
+
+    #[test]
+    fn test_schedule_full() {
+        let queue = ScheduleQueue::with_capacity(2);
+        let mut schedule = Schedule::with_capacity(2);
+        queue.push(0, ScheduledParam::PitchScale(1.0)).unwrap();
+        schedule.receive(&queue);
+        queue.push(0, ScheduledParam::FormantScale(1.0)).unwrap();
+        // Received changes still count until they are applied
+        assert!(matches!(
+            queue.push(0, ScheduledParam::PitchScale(1.0)),
+            Err(RubberBandError::ScheduleFull(2))
+        ));
+
+        schedule.drain_before(&queue, 1, |_| {});
+        assert!(queue.push(0, ScheduledParam::PitchScale(1.0)).is_ok());
+
+        schedule.clear(&queue);
+        assert_eq!(schedule.next_frame(), None);
+        assert!(queue.push(0, ScheduledParam::PitchScale(1.0)).is_ok());
+        assert!(queue.push(0, ScheduledParam::PitchScale(1.0)).is_ok());
+
+        let empty = ScheduleQueue::with_capacity(0);
+        assert!(matches!(empty.push(0, ScheduledParam::PitchScale(1.0)), Err(RubberBandError::ScheduleFull(0))));
+    }
+
+    #[test]
+    fn test_schedule_concurrent_push() {
+        const PRODUCERS: usize = 4;
+        const CHANGES: usize = 1000;
+
+        let queue = ScheduleQueue::with_capacity(8);
+        let mut schedule = Schedule::with_capacity(8);
+        let mut received = Vec::new();
+        std::thread::scope(|scope| {
+            for producer in 0..PRODUCERS {
+                let queue = &queue;
+                scope.spawn(move || {
+                    for change in 0..CHANGES {
+                        let scale = (producer * CHANGES + change) as f64;
+                        while queue.push(0, ScheduledParam::PitchScale(scale)).is_err() {
+                            std::thread::yield_now();
+                        }
+                    }
+                });
+            }
+            while received.len() < PRODUCERS * CHANGES {
+                schedule.receive(&queue);
+                schedule.drain_before(&queue, 1, |param| received.push(pitch(param) as usize));
+            }
+        });
+
+        // Every change arrives exactly once, in order for each producer
+        for producer in 0..PRODUCERS {
+            let changes: Vec<_> = received.iter().filter(|&&value| value / CHANGES == producer).collect();
+            assert_eq!(changes.len(), CHANGES);
+            assert!(changes.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+}
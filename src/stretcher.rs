@@ -0,0 +1,2561 @@
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use atomic_float::AtomicF64;
+use parking_lot::Mutex;
+
+use rubberband_sys::{
+    rubberband_delete,
+    rubberband_set_debug_level,
+    rubberband_get_engine_version,
+    rubberband_get_channel_count,
+    rubberband_get_samples_required,
+    rubberband_get_start_delay,
+    rubberband_get_preferred_start_pad,
+    rubberband_get_process_size_limit,
+    rubberband_shim_new,
+    rubberband_shim_set_time_ratio,
+    rubberband_shim_set_pitch_scale,
+    rubberband_shim_study,
+    rubberband_shim_process,
+    rubberband_shim_retrieve,
+    rubberband_shim_available,
+    rubberband_shim_reset,
+    rubberband_shim_set_key_frame_map,
+    rubberband_shim_set_transients_option,
+    rubberband_shim_set_detector_option,
+    rubberband_shim_set_phase_option,
+    rubberband_shim_set_pitch_option,
+    rubberband_shim_set_expected_input_duration,
+    rubberband_shim_set_max_process_size,
+    RubberBandState,
+    RubberBandOption,
+    RubberBandOptions,
+    RubberBandOption_RubberBandOptionProcessOffline as OPTION_BITS_PROCESS_OFFLINE,
+    RubberBandOption_RubberBandOptionProcessRealTime as OPTION_BITS_PROCESS_REALTIME,
+    RubberBandOption_RubberBandOptionEngineFaster as OPTION_BITS_ENGINE_FASTER,
+    RubberBandOption_RubberBandOptionEngineFiner as OPTION_BITS_ENGINE_FINER,
+    RubberBandOption_RubberBandOptionTransientsCrisp as OPTION_BITS_TRANSIENTS_CRISP,
+    RubberBandOption_RubberBandOptionTransientsMixed as OPTION_BITS_TRANSIENTS_MIXED,
+    RubberBandOption_RubberBandOptionTransientsSmooth as OPTION_BITS_TRANSIENTS_SMOOTH,
+    RubberBandOption_RubberBandOptionDetectorCompound as OPTION_BITS_DETECTOR_COMPOUND,
+    RubberBandOption_RubberBandOptionDetectorPercussive as OPTION_BITS_DETECTOR_PERCUSSIVE,
+    RubberBandOption_RubberBandOptionDetectorSoft as OPTION_BITS_DETECTOR_SOFT,
+    RubberBandOption_RubberBandOptionPhaseLaminar as OPTION_BITS_PHASE_LAMINAR,
+    RubberBandOption_RubberBandOptionPhaseIndependent as OPTION_BITS_PHASE_INDEPENDENT,
+    RubberBandOption_RubberBandOptionPitchHighSpeed as OPTION_BITS_PITCH_HIGH_SPEED,
+    RubberBandOption_RubberBandOptionPitchHighQuality as OPTION_BITS_PITCH_HIGH_QUALITY,
+    RubberBandOption_RubberBandOptionPitchHighConsistency as OPTION_BITS_PITCH_HIGH_CONSISTENCY,
+    RubberBandOption_RubberBandOptionThreadingAuto as OPTION_BITS_THREADING_AUTO,
+    RubberBandOption_RubberBandOptionThreadingNever as OPTION_BITS_THREADING_NEVER,
+    RubberBandOption_RubberBandOptionThreadingAlways as OPTION_BITS_THREADING_ALWAYS,
+    NO_THREADING,
+};
+
+use crate::schedule::{Schedule, ScheduleQueue};
+use crate::{check_pitch_scale, check_status, RubberBandError, ScheduledChange};
+
+/// Processing mode options for [Stretcher].
+///
+/// Note that this option **cannot** be changed once the [Stretcher] instance is created.
+/// It must be set via the [StretcherBuilder].
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherProcessMode};
+///
+/// let stretcher = StretcherBuilder::new(44100, 1)
+///     .unwrap()
+///     .process_mode(StretcherProcessMode::RealTime)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretcherProcessMode {
+    /// Offline mode, where the whole input is studied before it is processed. Gives the best
+    /// quality and an exact output duration. Default option.
+    Offline,
+    /// Real-time mode, where the input is streamed through [Stretcher::process()] with no study
+    /// pass. The time ratio and pitch scale may be changed while processing.
+    RealTime,
+}
+
+/// Processing engine options for [Stretcher].
+///
+/// Note that this option **cannot** be changed once the [Stretcher] instance is created.
+/// It must be set via the [StretcherBuilder]. Use [Stretcher::engine_version()] to check which
+/// engine was actually built.
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherEngine};
+///
+/// let stretcher = StretcherBuilder::new(44100, 1)
+///     .unwrap()
+///     .engine(StretcherEngine::Finer)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(stretcher.engine_version(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretcherEngine {
+    /// The R2 engine. Much cheaper in CPU, with good results on most material. Default option.
+    Faster,
+    /// The R3 engine. Higher quality, especially for polyphonic material and larger ratios, at a
+    /// much higher CPU cost.
+    Finer,
+}
+
+/// Transient handling options for [Stretcher].
+///
+/// This option only has an effect with the R2 ([Faster](StretcherEngine::Faster)) engine; the R3
+/// engine handles transients its own way. It can be set initially via the [StretcherBuilder] and,
+/// in real-time mode only, changed at any time using [Stretcher::set_transients_option()].
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherProcessMode, StretcherTransients};
+///
+/// let stretcher = StretcherBuilder::new(44100, 1)
+///     .unwrap()
+///     .process_mode(StretcherProcessMode::RealTime)
+///     .transients(StretcherTransients::Smooth)
+///     .build()
+///     .unwrap();
+///
+/// // Switch to crisp transients for a drum stem
+/// stretcher.set_transients_option(StretcherTransients::Crisp).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretcherTransients {
+    /// Reset component phases at detected transients, for crisp, clear attacks. Default option.
+    Crisp,
+    /// Reset component phases at transients only in the higher frequencies, keeping the lower
+    /// frequencies smooth.
+    Mixed,
+    /// Do not reset component phases at transients, giving a smoother, softer result.
+    Smooth,
+}
+
+/// Transient detector options for [Stretcher].
+///
+/// This option only has an effect with the R2 ([Faster](StretcherEngine::Faster)) engine. It can
+/// be set initially via the [StretcherBuilder] and, in real-time mode only, changed at any time
+/// using [Stretcher::set_detector_option()].
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherDetector};
+///
+/// let stretcher = StretcherBuilder::new(44100, 1)
+///     .unwrap()
+///     .detector(StretcherDetector::Percussive)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretcherDetector {
+    /// A general-purpose detector, good for most material. Default option.
+    Compound,
+    /// A detector tuned for percussive transients, with less sensitivity to other material.
+    Percussive,
+    /// A detector that is less likely to report transients, for smoother material.
+    Soft,
+}
+
+/// Phase continuity options for [Stretcher].
+///
+/// This option only has an effect with the R2 ([Faster](StretcherEngine::Faster)) engine. It can
+/// be set initially via the [StretcherBuilder] or changed at any time, in either processing mode,
+/// using [Stretcher::set_phase_option()].
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherPhase};
+///
+/// let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+///
+/// // Change the phase option
+/// stretcher.set_phase_option(StretcherPhase::Independent).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretcherPhase {
+    /// Keep the phases of adjacent frequency bins continuous, for the clearest sound at moderate
+    /// ratios. Default option.
+    Laminar,
+    /// Adjust the phase of each frequency bin independently, giving a more diffuse sound that
+    /// can suit larger stretches.
+    Independent,
+}
+
+/// Pitch shifting quality options for [Stretcher].
+///
+/// This option can be set initially via the [StretcherBuilder] and, in real-time mode only,
+/// changed at any time using [Stretcher::set_pitch_option()].
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherPitchMode, StretcherProcessMode};
+///
+/// let stretcher = StretcherBuilder::new(44100, 1)
+///     .unwrap()
+///     .process_mode(StretcherProcessMode::RealTime)
+///     .pitch_mode(StretcherPitchMode::HighConsistency)
+///     .build()
+///     .unwrap();
+///
+/// // Change the pitch mode
+/// stretcher.set_pitch_option(StretcherPitchMode::HighQuality).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretcherPitchMode {
+    /// Favour CPU cost over sound quality. Suitable for time stretching only, or for fixed pitch
+    /// shifts where CPU usage matters. Default option.
+    HighSpeed,
+    /// Favour sound quality over CPU cost. Suitable for fixed pitch shifts where quality matters.
+    HighQuality,
+    /// Favour consistency for pitch scales that vary around 1.0, avoiding discontinuities as the
+    /// pitch scale crosses 1.0. Suitable for real-time pitch automation. Real-time mode only.
+    HighConsistency,
+}
+
+/// Threading options for [Stretcher].
+///
+/// The R2 ([Faster](StretcherEngine::Faster)) engine can process the channels of multichannel
+/// audio in separate worker threads, in offline mode only. This option **cannot** be changed once
+/// the [Stretcher] instance is created. It must be set via the [StretcherBuilder].
+///
+/// If the library was built with the `no-threading` feature, no threads are ever started:
+/// [Auto](Self::Auto) then behaves as [Never](Self::Never), and [Always](Self::Always) is
+/// rejected by [StretcherBuilder::build()] with
+/// [`ThreadingUnavailable`](RubberBandError::ThreadingUnavailable).
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherThreading};
+///
+/// // Render deterministically on the calling thread
+/// let stretcher = StretcherBuilder::new(44100, 2)
+///     .unwrap()
+///     .threading(StretcherThreading::Never)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StretcherThreading {
+    /// Use worker threads if the stretcher is in offline mode with more than one channel, and the
+    /// system has more than one CPU. Default option.
+    Auto,
+    /// Never use worker threads.
+    Never,
+    /// Use worker threads whenever the mode and channel count allow it, even on a single CPU.
+    Always,
+}
+
+/// Builder for configuring and creating a [Stretcher] instance.
+///
+/// Provides methods to set options like the processing mode, engine, transient handling, initial
+/// time ratio and pitch scale, and debug level before constructing the `Stretcher`.
+///
+/// # Examples
+///
+/// ```
+/// use rubberband::StretcherBuilder;
+///
+/// let stretcher = StretcherBuilder::new(44100, 2)
+///     .unwrap()
+///     .time_ratio(1.5)
+///     .pitch_scale(0.8)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct StretcherBuilder {
+    /// The sample rate of the audio.
+    sample_rate: u32,
+    /// The number of channels of the audio.
+    channels: u32,
+    /// The processing mode of the stretcher.
+    process_mode: StretcherProcessMode,
+    /// The processing engine of the stretcher.
+    engine: StretcherEngine,
+    /// The transient handling option of the stretcher.
+    transients: StretcherTransients,
+    /// The transient detector option of the stretcher.
+    detector: StretcherDetector,
+    /// The phase continuity option of the stretcher.
+    phase: StretcherPhase,
+    /// The pitch shifting quality option of the stretcher.
+    pitch_mode: StretcherPitchMode,
+    /// The threading option of the stretcher.
+    threading: StretcherThreading,
+    /// The initial time ratio of the stretcher.
+    time_ratio: f64,
+    /// The initial pitch scale of the stretcher.
+    pitch_scale: f64,
+    /// The debug level of the stretcher.
+    debug_level: i32,
+    /// The maximum number of pending scheduled parameter changes.
+    schedule_capacity: usize,
+}
+
+impl StretcherBuilder {
+    /// Create a new StretcherBuilder.
+    ///
+    /// Initializes the builder with default options:
+    /// - Process Mode: [StretcherProcessMode::Offline]
+    /// - Engine: [StretcherEngine::Faster]
+    /// - Transients: [StretcherTransients::Crisp]
+    /// - Detector: [StretcherDetector::Compound]
+    /// - Phase: [StretcherPhase::Laminar]
+    /// - Pitch Mode: [StretcherPitchMode::HighSpeed]
+    /// - Threading: [StretcherThreading::Auto]
+    /// - Time Ratio: 1.0
+    /// - Pitch Scale: 1.0
+    /// - Debug Level: 0
+    /// - Schedule Capacity: 64
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate`: The sample rate of the audio (must be > 0).
+    /// * `channels`: The number of channels of the audio (must be > 0).
+    pub fn new(sample_rate: u32, channels: u32) -> Result<Self, RubberBandError> {
+        if sample_rate == 0 {
+            return Err(RubberBandError::UnsupportedSampleRate(sample_rate));
+        }
+        if channels == 0 {
+            return Err(RubberBandError::UnsupportedChannelCount(channels));
+        }
+        Ok(Self {
+            sample_rate,
+            channels,
+            process_mode: StretcherProcessMode::Offline,
+            engine: StretcherEngine::Faster,
+            transients: StretcherTransients::Crisp,
+            detector: StretcherDetector::Compound,
+            phase: StretcherPhase::Laminar,
+            pitch_mode: StretcherPitchMode::HighSpeed,
+            threading: StretcherThreading::Auto,
+            time_ratio: 1.0,
+            pitch_scale: 1.0,
+            debug_level: 0,
+            schedule_capacity: 64,
+        })
+    }
+
+    /// Set the processing mode of [Stretcher].
+    ///
+    /// This option **cannot** be changed once the [Stretcher] instance is created.
+    /// Defaults to [StretcherProcessMode::Offline].
+    ///
+    /// # Arguments
+    ///
+    /// * `mode`: The processing mode of the stretcher.
+    pub fn process_mode(mut self, mode: StretcherProcessMode) -> Self {
+        self.process_mode = mode;
+        self
+    }
+
+    /// Set the processing engine of [Stretcher].
+    ///
+    /// This option **cannot** be changed once the [Stretcher] instance is created.
+    /// Defaults to [StretcherEngine::Faster].
+    ///
+    /// # Arguments
+    ///
+    /// * `engine`: The processing engine of the stretcher.
+    pub fn engine(mut self, engine: StretcherEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Set the transient handling option of [Stretcher].
+    ///
+    /// Only the default can be used with [StretcherEngine::Finer]. In real-time mode, this option
+    /// can be changed later using [Stretcher::set_transients_option()].
+    /// Defaults to [StretcherTransients::Crisp].
+    ///
+    /// # Arguments
+    ///
+    /// * `transients`: The transient handling option of the stretcher.
+    pub fn transients(mut self, transients: StretcherTransients) -> Self {
+        self.transients = transients;
+        self
+    }
+
+    /// Set the transient detector option of [Stretcher].
+    ///
+    /// Only the default can be used with [StretcherEngine::Finer]. In real-time mode, this option
+    /// can be changed later using [Stretcher::set_detector_option()].
+    /// Defaults to [StretcherDetector::Compound].
+    ///
+    /// # Arguments
+    ///
+    /// * `detector`: The transient detector option of the stretcher.
+    pub fn detector(mut self, detector: StretcherDetector) -> Self {
+        self.detector = detector;
+        self
+    }
+
+    /// Set the phase continuity option of [Stretcher].
+    ///
+    /// Only the default can be used with [StretcherEngine::Finer]. This option can be changed
+    /// later using [Stretcher::set_phase_option()].
+    /// Defaults to [StretcherPhase::Laminar].
+    ///
+    /// # Arguments
+    ///
+    /// * `phase`: The phase continuity option of the stretcher.
+    pub fn phase(mut self, phase: StretcherPhase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Set the pitch shifting quality option of [Stretcher].
+    ///
+    /// [StretcherPitchMode::HighConsistency] can only be used in real-time mode. In real-time
+    /// mode, this option can be changed later using [Stretcher::set_pitch_option()].
+    /// Defaults to [StretcherPitchMode::HighSpeed].
+    ///
+    /// # Arguments
+    ///
+    /// * `pitch_mode`: The pitch shifting quality option of the stretcher.
+    pub fn pitch_mode(mut self, pitch_mode: StretcherPitchMode) -> Self {
+        self.pitch_mode = pitch_mode;
+        self
+    }
+
+    /// Set the threading option of [Stretcher].
+    ///
+    /// This option **cannot** be changed once the [Stretcher] instance is created.
+    /// [StretcherThreading::Always] cannot be used if the library was built with the
+    /// `no-threading` feature. Defaults to [StretcherThreading::Auto].
+    ///
+    /// # Arguments
+    ///
+    /// * `threading`: The threading option of the stretcher.
+    pub fn threading(mut self, threading: StretcherThreading) -> Self {
+        self.threading = threading;
+        self
+    }
+
+    /// Set the initial time ratio of [Stretcher].
+    ///
+    /// The time ratio is the ratio of the output duration to the input duration (e.g., 2.0 for
+    /// twice as long, 0.5 for half as long). Defaults to 1.0.
+    ///
+    /// This option can be changed later using [Stretcher::set_time_ratio()].
+    ///
+    /// # Arguments
+    ///
+    /// * `ratio`: The time ratio (must be finite and > 0).
+    pub fn time_ratio(mut self, ratio: f64) -> Self {
+        self.time_ratio = ratio;
+        self
+    }
+
+    /// Set the initial pitch scale of [Stretcher].
+    ///
+    /// The pitch scale is the ratio of the target frequency to the source frequency (e.g., 2.0 for
+    /// one octave up, 0.5 for one octave down). Defaults to 1.0.
+    ///
+    /// This option can be changed later using [Stretcher::set_pitch_scale()].
+    ///
+    /// # Arguments
+    ///
+    /// * `scale`: The pitch scale (must be finite and > 0).
+    pub fn pitch_scale(mut self, scale: f64) -> Self {
+        self.pitch_scale = scale;
+        self
+    }
+
+    /// Set the debug level of the stretcher.
+    ///
+    /// The default is 0. The higher the level, the more verbose the output. See the C++
+    /// documentation for `RubberBandStretcher::setDebugLevel` for details on the levels.
+    ///
+    /// # Arguments
+    ///
+    /// * `level`: The debug level of the stretcher.
+    pub fn debug_level(mut self, level: i32) -> Self {
+        self.debug_level = level;
+        self
+    }
+
+    /// Set the maximum number of pending scheduled parameter changes.
+    ///
+    /// The queue used by [Stretcher::schedule_time_ratio()] and
+    /// [Stretcher::schedule_pitch_scale()] is allocated when the stretcher is built, so that
+    /// scheduling and processing never allocate. The default is 64.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: The maximum number of pending scheduled changes.
+    pub fn schedule_capacity(mut self, capacity: usize) -> Self {
+        self.schedule_capacity = capacity;
+        self
+    }
+
+    /// Build the [Stretcher] with the configured options.
+    ///
+    /// # Returns
+    ///
+    /// A new [Stretcher] instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The time ratio is not a finite positive number ([`InvalidTimeRatio`](RubberBandError::InvalidTimeRatio)).
+    /// - The pitch scale is not a finite positive number ([`InvalidPitchScale`](RubberBandError::InvalidPitchScale)).
+    /// - A non-default transients, detector or phase option is combined with
+    ///   [StretcherEngine::Finer], which ignores them ([`UnsupportedByEngine`](RubberBandError::UnsupportedByEngine)).
+    /// - [StretcherPitchMode::HighConsistency] is combined with offline mode ([`RealTimeOnly`](RubberBandError::RealTimeOnly)).
+    /// - [StretcherThreading::Always] is set, but the library was built with the `no-threading`
+    ///   feature ([`ThreadingUnavailable`](RubberBandError::ThreadingUnavailable)).
+    /// - The C++ library ran out of memory ([`OutOfMemory`](RubberBandError::OutOfMemory)) or
+    ///   failed otherwise ([`InvalidArgument`](RubberBandError::InvalidArgument),
+    ///   [`LibraryException`](RubberBandError::LibraryException)).
+    /// - The underlying C++ instance could not be created ([`CreationFailed`](RubberBandError::CreationFailed)).
+    pub fn build(self) -> Result<Stretcher, RubberBandError> {
+        check_time_ratio(self.time_ratio)?;
+        check_pitch_scale(self.pitch_scale)?;
+        if self.engine == StretcherEngine::Finer {
+            if self.transients != StretcherTransients::Crisp {
+                return Err(RubberBandError::UnsupportedByEngine { option: "transients", engine: self.engine });
+            }
+            if self.detector != StretcherDetector::Compound {
+                return Err(RubberBandError::UnsupportedByEngine { option: "detector", engine: self.engine });
+            }
+            if self.phase != StretcherPhase::Laminar {
+                return Err(RubberBandError::UnsupportedByEngine { option: "phase", engine: self.engine });
+            }
+        }
+
+        if self.pitch_mode == StretcherPitchMode::HighConsistency
+            && self.process_mode != StretcherProcessMode::RealTime
+        {
+            return Err(RubberBandError::RealTimeOnly("StretcherPitchMode::HighConsistency"));
+        }
+        if NO_THREADING && self.threading == StretcherThreading::Always {
+            return Err(RubberBandError::ThreadingUnavailable);
+        }
+
+        let mut options: RubberBandOption = 0; // Default options
+        match self.process_mode {
+            StretcherProcessMode::Offline => options |= OPTION_BITS_PROCESS_OFFLINE,
+            StretcherProcessMode::RealTime => options |= OPTION_BITS_PROCESS_REALTIME,
+        }
+        match self.engine {
+            StretcherEngine::Faster => options |= OPTION_BITS_ENGINE_FASTER,
+            StretcherEngine::Finer => options |= OPTION_BITS_ENGINE_FINER,
+        }
+        options |= transients_bits(self.transients);
+        options |= detector_bits(self.detector);
+        options |= phase_bits(self.phase);
+        options |= pitch_mode_bits(self.pitch_mode);
+        options |= threading_bits(self.threading);
+
+        let mut state: RubberBandState = std::ptr::null_mut();
+        check_status(unsafe {
+            rubberband_shim_new(
+                self.sample_rate,
+                self.channels,
+                options as RubberBandOptions,
+                self.time_ratio,
+                self.pitch_scale,
+                &mut state,
+            )
+        })?;
+        if state.is_null() {
+            return Err(RubberBandError::CreationFailed);
+        }
+        unsafe {
+            rubberband_set_debug_level(state, self.debug_level);
+        }
+
+        Ok(Stretcher {
+            state,
+            mutex: Mutex::new(Progress {
+                stage: Stage::Idle,
+                studied: 0,
+                expected_duration: None,
+                max_process_size: None,
+                input_ptrs: vec![std::ptr::null(); self.channels as usize],
+                output_ptrs: vec![std::ptr::null_mut(); self.channels as usize],
+                frame: 0,
+                schedule: Schedule::with_capacity(self.schedule_capacity),
+            }),
+            sample_rate: self.sample_rate,
+            process_mode: self.process_mode,
+            time_ratio: AtomicF64::new(self.time_ratio),
+            time_dirty: AtomicBool::new(false),
+            pitch_scale: AtomicF64::new(self.pitch_scale),
+            pitch_dirty: AtomicBool::new(false),
+            transients_option: DeferredOption::new(),
+            detector_option: DeferredOption::new(),
+            phase_option: DeferredOption::new(),
+            pitch_option: DeferredOption::new(),
+            schedule: ScheduleQueue::with_capacity(self.schedule_capacity),
+        })
+    }
+}
+
+/// An option change stored by a setter and passed to the C++ instance on the next processing call.
+struct DeferredOption {
+    bits: AtomicI32,
+    dirty: AtomicBool,
+}
+
+impl DeferredOption {
+    fn new() -> Self {
+        Self {
+            bits: AtomicI32::new(0),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Store new option bits, replacing any change that has not been applied yet.
+    fn store(&self, bits: RubberBandOption) {
+        self.bits.store(bits as RubberBandOptions, Ordering::Relaxed);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Take the pending option bits, if there are any.
+    fn take(&self) -> Option<RubberBandOptions> {
+        if self.dirty.swap(false, Ordering::Acquire) {
+            Some(self.bits.load(Ordering::Relaxed))
+        } else {
+            None
+        }
+    }
+
+    /// Mark the option as pending again after the taken bits failed to apply, so that the latest
+    /// stored bits are retried on the next processing call.
+    fn retry(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+}
+
+/// A [Stretcher] parameter value to be applied at a scheduled frame.
+#[derive(Debug, Clone, Copy)]
+enum StretchParam {
+    TimeRatio(f64),
+    PitchScale(f64),
+}
+
+/// The progress of the offline study / process workflow, guarded by the processing lock.
+struct Progress {
+    /// The current stage of the workflow.
+    stage: Stage,
+    /// The number of samples (per channel) studied so far.
+    studied: usize,
+    /// The input duration set with `set_expected_input_duration`, if any.
+    expected_duration: Option<usize>,
+    /// The maximum block size set with `set_max_process_size`, if any.
+    max_process_size: Option<usize>,
+    /// Channel pointer arrays passed to the C API, allocated at build time so that processing
+    /// does not allocate.
+    input_ptrs: Vec<*const f32>,
+    output_ptrs: Vec<*mut f32>,
+    /// The number of input frames processed since the last reset.
+    frame: u64,
+    /// Parameter changes received from the `schedule` queue, at future input frames.
+    schedule: Schedule<StretchParam>,
+}
+
+impl Progress {
+    /// The input duration, if it is known: either set explicitly, or fully studied.
+    fn known_duration(&self) -> Option<usize> {
+        self.expected_duration.or((self.stage == Stage::Studied).then_some(self.studied))
+    }
+}
+
+/// The stage of the offline study / process workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// Nothing has been studied or processed since creation or the last reset.
+    Idle,
+    /// Some, but not all, of the input has been studied.
+    Studying,
+    /// The final block has been studied.
+    Studied,
+    /// Some, but not all, of the input has been processed.
+    Processing,
+    /// The final block has been processed.
+    Finished,
+}
+
+/// A time stretcher and pitch shifter using the RubberBand audio processing library.
+///
+/// This struct wraps the C++ `RubberBandStretcher`. Unlike [LiveShifter](crate::LiveShifter),
+/// it can change the duration of the audio as well as its pitch, and accepts any number of
+/// samples per call. It runs in one of two modes, chosen with [StretcherBuilder::process_mode()].
+///
+/// ## Offline Mode
+///
+/// Offline processing is a two-pass workflow:
+///
+/// 1. **Study:** Pass the whole input through [study()](Self::study()), marking the last block
+///    as final. This lets the stretcher analyse the material and plan the stretch.
+/// 2. **Process:** Pass the whole input again through [process()](Self::process()), marking the
+///    last block as final, and collect the output with [available()](Self::available()) and
+///    [retrieve_into()](Self::retrieve_into()) as it becomes ready.
+///
+/// The study pass may be skipped entirely, at some cost in quality, but once studying has started
+/// it must be finished before processing. The time ratio and pitch scale can only take effect
+/// before the first `study` or `process` call; later changes are held until [reset()](Self::reset()).
+///
+/// ## Real-time Mode
+///
+/// Real-time processing is a single streaming pass with no study. Each cycle, ask
+/// [samples_required()](Self::samples_required()) how much input the stretcher needs to produce
+/// more output, pass that much to [process()](Self::process()), then collect whatever is
+/// [available](Self::available()). The time ratio and pitch scale may be changed at any time with
+/// [set_time_ratio()](Self::set_time_ratio()) and [set_pitch_scale()](Self::set_pitch_scale()).
+/// The output starts [start_delay()](Self::start_delay()) samples late relative to the input.
+///
+/// Create instances using the [StretcherBuilder].
+///
+/// # Thread Safety
+///
+/// This type implements `Send` and `Sync`. As with [LiveShifter](crate::LiveShifter), the calls
+/// that touch the processing state (`study`, `process`, `retrieve`, `retrieve_into`) are made
+/// mutually exclusive by an internal `Mutex`. Concurrent calls will return
+/// [`OperationInProgress`](RubberBandError::OperationInProgress) instead of blocking.
+///
+/// The time ratio, pitch scale and option setters use atomic variables to store the new values
+/// without locking, so they are safe to call concurrently with processing. The new values take
+/// effect on the next `process`, `samples_required` or `start_delay` call.
+///
+/// # Examples
+///
+/// Offline stretching:
+///
+/// ```
+/// use rubberband::StretcherBuilder;
+///
+/// // Stretch a mono signal to twice its duration
+/// let stretcher = StretcherBuilder::new(44100, 1)
+///     .unwrap()
+///     .time_ratio(2.0)
+///     .build()
+///     .unwrap();
+///
+/// let input: Vec<f32> = (0..44100).map(|n| (n as f32 * 0.05).sin()).collect();
+/// let input_buffers: [&[f32]; 1] = [&input];
+///
+/// // First pass: study the whole input
+/// stretcher.study(&input_buffers, true).unwrap();
+///
+/// // Second pass: process the whole input
+/// stretcher.process(&input_buffers, true).unwrap();
+///
+/// // Collect the output until the stretcher is finished. With threaded processing, more output
+/// // may still be on its way while none is available
+/// let mut output: Vec<f32> = Vec::new();
+/// while let Some(available) = stretcher.available().unwrap() {
+///     if available == 0 {
+///         std::thread::yield_now();
+///         continue;
+///     }
+///     let block = stretcher.retrieve().unwrap();
+///     output.extend_from_slice(&block[0]);
+/// }
+/// ```
+///
+/// Real-time stretching, changing the tempo while running:
+///
+/// ```
+/// use rubberband::{StretcherBuilder, StretcherProcessMode};
+///
+/// let stretcher = StretcherBuilder::new(48000, 1)
+///     .unwrap()
+///     .process_mode(StretcherProcessMode::RealTime)
+///     .build()
+///     .unwrap();
+///
+/// let mut output = vec![0.0f32; 1024];
+/// for cycle in 0..100 {
+///     // Slow down gradually
+///     stretcher.set_time_ratio(1.0 + cycle as f64 / 100.0);
+///
+///     // Feed as much input as the stretcher asks for
+///     let input = vec![0.1f32; stretcher.samples_required()];
+///     stretcher.process(&[&input], false).unwrap();
+///
+///     // Drain the output
+///     while stretcher.available().unwrap().unwrap_or(0) > 0 {
+///         let retrieved = stretcher.retrieve_into(&mut [&mut output]).unwrap();
+///         // output[..retrieved] now contains the stretched audio
+///     }
+/// }
+/// ```
+pub struct Stretcher {
+    state: RubberBandState,
+    mutex: Mutex<Progress>,
+    sample_rate: u32,
+    process_mode: StretcherProcessMode,
+    time_ratio: AtomicF64,
+    time_dirty: AtomicBool,
+    pitch_scale: AtomicF64,
+    pitch_dirty: AtomicBool,
+    transients_option: DeferredOption,
+    detector_option: DeferredOption,
+    phase_option: DeferredOption,
+    pitch_option: DeferredOption,
+    /// Parameter changes scheduled at future input frames, received at the start of each
+    /// processing call.
+    schedule: ScheduleQueue<StretchParam>,
+}
+
+impl Stretcher {
+    /// Get the sample rate of the [Stretcher].
+    ///
+    /// # Returns
+    ///
+    /// The sample rate of the [Stretcher].
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get the number of channels the [Stretcher] was configured for.
+    ///
+    /// # Returns
+    ///
+    /// The number of audio channels.
+    pub fn channel_count(&self) -> u32 {
+        unsafe {
+            rubberband_get_channel_count(self.state)
+        }
+    }
+
+    /// Get the processing mode the [Stretcher] was configured for.
+    ///
+    /// # Returns
+    ///
+    /// The [StretcherProcessMode] of the stretcher.
+    pub fn process_mode(&self) -> StretcherProcessMode {
+        self.process_mode
+    }
+
+    /// Get the version of the processing engine the [Stretcher] is using.
+    ///
+    /// This reports the engine that was actually built, which is normally the one requested with
+    /// [StretcherBuilder::engine()].
+    ///
+    /// # Returns
+    ///
+    /// `2` for the R2 ([Faster](StretcherEngine::Faster)) engine, or `3` for the R3
+    /// ([Finer](StretcherEngine::Finer)) engine.
+    pub fn engine_version(&self) -> i32 {
+        unsafe {
+            rubberband_get_engine_version(self.state)
+        }
+    }
+
+    /// Get the processing engine the [Stretcher] is using.
+    ///
+    /// This is [engine_version()](Self::engine_version()) as a [StretcherEngine].
+    ///
+    /// # Returns
+    ///
+    /// The [StretcherEngine] that was actually built.
+    pub fn engine(&self) -> StretcherEngine {
+        if self.engine_version() >= 3 {
+            StretcherEngine::Finer
+        } else {
+            StretcherEngine::Faster
+        }
+    }
+
+    /// Set the time ratio of the [Stretcher].
+    ///
+    /// The time ratio is the ratio of the output duration to the input duration (e.g., 2.0 for
+    /// twice as long, 0.5 for half as long, 1.0 for no change).
+    ///
+    /// This method uses atomic operations and is safe to call concurrently with processing or
+    /// other methods. In real-time mode, the change will take effect on the next processing call.
+    /// In offline mode, it only takes effect if no input has been studied or processed yet (or
+    /// after [reset()](Self::reset())).
+    ///
+    /// # Arguments
+    ///
+    /// * `ratio`: The desired time ratio. This is not validated; use
+    ///   [try_set_time_ratio()](Self::try_set_time_ratio()) for values that may be out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::{StretcherBuilder, StretcherProcessMode};
+    ///
+    /// let stretcher = StretcherBuilder::new(44100, 1)
+    ///     .unwrap()
+    ///     .process_mode(StretcherProcessMode::RealTime)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Play at 80% speed
+    /// stretcher.set_time_ratio(1.25);
+    /// assert_eq!(stretcher.time_ratio(), 1.25);
+    /// ```
+    pub fn set_time_ratio(&self, ratio: f64) {
+        self.time_ratio.store(ratio, Ordering::Relaxed);
+        self.time_dirty.store(true, Ordering::Release);
+    }
+
+    /// Set the time ratio of the [Stretcher], checking that it is valid.
+    ///
+    /// Works as [set_time_ratio()](Self::set_time_ratio()), but rejects values that the C++
+    /// library cannot handle instead of passing them on.
+    ///
+    /// # Arguments
+    ///
+    /// * `ratio`: The desired time ratio.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidTimeRatio`](RubberBandError::InvalidTimeRatio) if the ratio is not a
+    /// finite number greater than 0. The time ratio is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::{RubberBandError, StretcherBuilder};
+    ///
+    /// let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+    ///
+    /// assert!(stretcher.try_set_time_ratio(1.5).is_ok());
+    /// assert!(matches!(
+    ///     stretcher.try_set_time_ratio(0.0),
+    ///     Err(RubberBandError::InvalidTimeRatio(_))
+    /// ));
+    /// assert_eq!(stretcher.time_ratio(), 1.5);
+    /// ```
+    pub fn try_set_time_ratio(&self, ratio: f64) -> Result<(), RubberBandError> {
+        check_time_ratio(ratio)?;
+        self.set_time_ratio(ratio);
+        Ok(())
+    }
+
+    /// Get the current target time ratio of the [Stretcher].
+    ///
+    /// Note that the actual time ratio applied during processing might slightly lag if
+    /// `set_time_ratio` was called very recently from another thread.
+    ///
+    /// # Returns
+    ///
+    /// The ratio of the output duration to the input duration.
+    pub fn time_ratio(&self) -> f64 {
+        self.time_ratio.load(Ordering::Relaxed)
+    }
+
+    /// Set the pitch scale of the [Stretcher].
+    ///
+    /// The pitch scale is the ratio of the target frequency to the source frequency (e.g., 2.0 for
+    /// one octave up, 0.5 for one octave down, 1.0 for no change).
+    ///
+    /// This method uses atomic operations and is safe to call concurrently with processing or
+    /// other methods. In real-time mode, the change will take effect on the next processing call.
+    /// In offline mode, it only takes effect if no input has been studied or processed yet (or
+    /// after [reset()](Self::reset())).
+    ///
+    /// # Arguments
+    ///
+    /// * `scale`: The desired pitch scale (ratio). This is not validated; use
+    ///   [try_set_pitch_scale()](Self::try_set_pitch_scale()) for values that may be out of range.
+    pub fn set_pitch_scale(&self, scale: f64) {
+        self.pitch_scale.store(scale, Ordering::Relaxed);
+        self.pitch_dirty.store(true, Ordering::Release);
+    }
+
+    /// Set the pitch scale of the [Stretcher], checking that it is valid.
+    ///
+    /// Works as [set_pitch_scale()](Self::set_pitch_scale()), but rejects values that the C++
+    /// library cannot handle instead of passing them on.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale`: The desired pitch scale (ratio).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidPitchScale`](RubberBandError::InvalidPitchScale) if the scale is not a
+    /// finite number greater than 0. The pitch scale is left unchanged.
+    pub fn try_set_pitch_scale(&self, scale: f64) -> Result<(), RubberBandError> {
+        check_pitch_scale(scale)?;
+        self.set_pitch_scale(scale);
+        Ok(())
+    }
+
+    /// Get the current target pitch scale of the [Stretcher].
+    ///
+    /// Note that the actual pitch scale applied during processing might slightly lag if
+    /// `set_pitch_scale` was called very recently from another thread.
+    ///
+    /// # Returns
+    ///
+    /// The ratio of the target frequency to the source frequency.
+    pub fn pitch_scale(&self) -> f64 {
+        self.pitch_scale.load(Ordering::Relaxed)
+    }
+
+    /// Set the transient handling option of the [Stretcher] (real-time mode only).
+    ///
+    /// This method is safe to call concurrently with processing. The change will take effect on
+    /// the next processing call.
+    ///
+    /// # Arguments
+    ///
+    /// * `option`: The desired [StretcherTransients] option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The stretcher uses the R3 engine, which ignores this option ([`UnsupportedByEngine`](RubberBandError::UnsupportedByEngine)).
+    /// - The stretcher is in offline mode, where this option is fixed on construction ([`RealTimeOnly`](RubberBandError::RealTimeOnly)).
+    pub fn set_transients_option(&self, option: StretcherTransients) -> Result<(), RubberBandError> {
+        self.check_r2_option("transients")?;
+        if self.process_mode != StretcherProcessMode::RealTime {
+            return Err(RubberBandError::RealTimeOnly("set_transients_option"));
+        }
+        self.transients_option.store(transients_bits(option));
+        Ok(())
+    }
+
+    /// Set the transient detector option of the [Stretcher] (real-time mode only).
+    ///
+    /// This method is safe to call concurrently with processing. The change will take effect on
+    /// the next processing call.
+    ///
+    /// # Arguments
+    ///
+    /// * `option`: The desired [StretcherDetector] option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The stretcher uses the R3 engine, which ignores this option ([`UnsupportedByEngine`](RubberBandError::UnsupportedByEngine)).
+    /// - The stretcher is in offline mode, where this option is fixed on construction ([`RealTimeOnly`](RubberBandError::RealTimeOnly)).
+    pub fn set_detector_option(&self, option: StretcherDetector) -> Result<(), RubberBandError> {
+        self.check_r2_option("detector")?;
+        if self.process_mode != StretcherProcessMode::RealTime {
+            return Err(RubberBandError::RealTimeOnly("set_detector_option"));
+        }
+        self.detector_option.store(detector_bits(option));
+        Ok(())
+    }
+
+    /// Set the phase continuity option of the [Stretcher].
+    ///
+    /// This method is safe to call concurrently with processing. The change will take effect on
+    /// the next processing call.
+    ///
+    /// # Arguments
+    ///
+    /// * `option`: The desired [StretcherPhase] option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError::UnsupportedByEngine] if the stretcher uses the R3 engine, which
+    /// ignores this option.
+    pub fn set_phase_option(&self, option: StretcherPhase) -> Result<(), RubberBandError> {
+        self.check_r2_option("phase")?;
+        self.phase_option.store(phase_bits(option));
+        Ok(())
+    }
+
+    /// Set the pitch shifting quality option of the [Stretcher] (real-time mode only).
+    ///
+    /// This method is safe to call concurrently with processing. The change will take effect on
+    /// the next processing call.
+    ///
+    /// # Arguments
+    ///
+    /// * `option`: The desired [StretcherPitchMode] option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError::RealTimeOnly] if the stretcher is in offline mode, where this
+    /// option is fixed on construction.
+    pub fn set_pitch_option(&self, option: StretcherPitchMode) -> Result<(), RubberBandError> {
+        if self.process_mode != StretcherProcessMode::RealTime {
+            return Err(RubberBandError::RealTimeOnly("set_pitch_option"));
+        }
+        self.pitch_option.store(pitch_mode_bits(option));
+        Ok(())
+    }
+
+    /// Check that an option which only the R2 engine uses can be set.
+    fn check_r2_option(&self, option: &'static str) -> Result<(), RubberBandError> {
+        let engine = self.engine();
+        if engine != StretcherEngine::Faster {
+            return Err(RubberBandError::UnsupportedByEngine { option, engine });
+        }
+        Ok(())
+    }
+
+    /// Get the number of input samples (per channel) needed before more output can be produced.
+    ///
+    /// In real-time mode, pass this many samples to the next [process()](Self::process()) call to
+    /// keep the output flowing. The value depends on the time ratio and pitch scale, so pending
+    /// changes are applied first. In offline mode this is not needed and returns 0.
+    ///
+    /// **Note:** This method acquires the internal processing lock and will block while another
+    /// processing call is in progress on the same instance.
+    ///
+    /// # Returns
+    ///
+    /// The number of input samples required per channel.
+    pub fn samples_required(&self) -> usize {
+        let progress = self.mutex.lock();
+        unsafe {
+            // A change the C++ library fails to apply stays pending, to be retried and reported
+            // by the next processing call
+            let _ = self.apply_pending(progress.stage);
+            rubberband_get_samples_required(self.state) as usize
+        }
+    }
+
+    /// Get the start delay (in samples per channel) of the [Stretcher] in real-time mode.
+    ///
+    /// This indicates how many samples should be discarded from the beginning of the output to
+    /// align it temporally with the input signal. It depends on the time ratio and pitch scale, so
+    /// pending changes are applied first. In offline mode the output is already aligned and this
+    /// returns 0.
+    ///
+    /// **Note:** This method acquires the internal processing lock and will block while another
+    /// processing call is in progress on the same instance.
+    ///
+    /// # Returns
+    ///
+    /// The start delay in samples per channel.
+    pub fn start_delay(&self) -> u32 {
+        let progress = self.mutex.lock();
+        unsafe {
+            // A change the C++ library fails to apply stays pending, to be retried and reported
+            // by the next processing call
+            let _ = self.apply_pending(progress.stage);
+            rubberband_get_start_delay(self.state)
+        }
+    }
+
+    /// Get the amount of silence (in samples per channel) to feed in before the real input.
+    ///
+    /// In real-time mode, feeding this many zero samples before the first block of real input
+    /// (and then discarding [start_delay()](Self::start_delay()) samples of output) gives output
+    /// that is aligned with the input from its very first sample. In offline mode this returns 0.
+    ///
+    /// # Returns
+    ///
+    /// The preferred start padding in samples per channel.
+    pub fn preferred_start_pad(&self) -> u32 {
+        let _guard = self.mutex.lock();
+        unsafe {
+            rubberband_get_preferred_start_pad(self.state)
+        }
+    }
+
+    /// Study a block of audio samples (first pass of offline processing).
+    ///
+    /// The whole input should be passed through this method, in one or more blocks of any size,
+    /// before any of it is passed to [process()](Self::process()).
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: A slice of slices (`&[&[f32]]`), where each inner slice represents one channel
+    ///   of audio data.
+    ///   - The number of inner slices must equal [channel_count()](Self::channel_count()).
+    ///   - All inner slices must have the same length.
+    /// * `is_final`: Whether this is the last block of the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - Input channel count or block size is inconsistent ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - Processing has already started ([`AlreadyProcessing`](RubberBandError::AlreadyProcessing)).
+    /// - The final block has already been studied ([`AlreadyFinished`](RubberBandError::AlreadyFinished)).
+    /// - The stretcher is in real-time mode ([`OfflineOnly`](RubberBandError::OfflineOnly)).
+    /// - A concurrent processing call is in progress ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    pub fn study(&self, input: &[&[f32]], is_final: bool) -> Result<(), RubberBandError> {
+        if self.process_mode != StretcherProcessMode::Offline {
+            return Err(RubberBandError::OfflineOnly("study"));
+        }
+        let mut progress = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        match progress.stage {
+            Stage::Idle | Stage::Studying => {}
+            Stage::Studied => return Err(RubberBandError::AlreadyFinished),
+            Stage::Processing | Stage::Finished => {
+                return Err(RubberBandError::AlreadyProcessing("study"));
+            }
+        }
+
+        self.check_input(input)?;
+
+        unsafe {
+            self.apply_pending(progress.stage)?;
+        }
+        let input_ptrs = &mut progress.input_ptrs;
+        for_each_chunk(input[0].len(), is_final, |offset, len, chunk_is_final| unsafe {
+            point_at(input_ptrs, input, offset);
+            check_status(rubberband_shim_study(self.state, input_ptrs.as_ptr(), len, chunk_is_final as i32))
+        })?;
+
+        progress.studied += input[0].len();
+        progress.stage = if is_final { Stage::Studied } else { Stage::Studying };
+        Ok(())
+    }
+
+    /// Process a block of audio samples.
+    ///
+    /// In offline mode, this is the second pass, after [study()](Self::study()). In real-time mode,
+    /// pass about [samples_required()](Self::samples_required()) samples per call. Any pending
+    /// time ratio or pitch scale change is applied before processing, and the block is split at
+    /// the frames of [scheduled](Self::schedule_time_ratio()) changes.
+    ///
+    /// The processed output can be collected with [retrieve_into()](Self::retrieve_into()) or
+    /// [retrieve()](Self::retrieve()) as it becomes [available](Self::available()).
+    ///
+    /// This performs no heap allocation in the Rust wrapper, so in real-time mode it can be called
+    /// from an audio thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `input`: A slice of slices (`&[&[f32]]`), where each inner slice represents one channel
+    ///   of audio data.
+    ///   - The number of inner slices must equal [channel_count()](Self::channel_count()).
+    ///   - All inner slices must have the same length.
+    /// * `is_final`: Whether this is the last block of the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - Input channel count or block size is inconsistent ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - In real-time mode, the block is larger than the [maximum process size](Self::set_max_process_size())
+    ///   ([`TooManySamples`](RubberBandError::TooManySamples)).
+    /// - Studying was started but the final block was never studied ([`StudyIncomplete`](RubberBandError::StudyIncomplete)).
+    /// - The final block has already been processed ([`AlreadyFinished`](RubberBandError::AlreadyFinished)).
+    /// - A concurrent processing call is in progress ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    pub fn process(&self, input: &[&[f32]], is_final: bool) -> Result<(), RubberBandError> {
+        let mut progress = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        match progress.stage {
+            Stage::Idle | Stage::Studied | Stage::Processing => {}
+            Stage::Studying => return Err(RubberBandError::StudyIncomplete),
+            Stage::Finished => return Err(RubberBandError::AlreadyFinished),
+        }
+
+        self.check_input(input)?;
+        if self.process_mode == StretcherProcessMode::RealTime {
+            let limit = progress.max_process_size.unwrap_or_else(|| self.process_size_limit());
+            if input[0].len() > limit {
+                return Err(RubberBandError::TooManySamples {
+                    limit,
+                    actual: input[0].len(),
+                });
+            }
+        }
+
+        // Process up to each scheduled change in turn, applying it in between
+        let progress = &mut *progress;
+        let length = input[0].len();
+        let mut start = 0;
+        progress.schedule.receive(&self.schedule);
+        loop {
+            let frame = progress.frame + start as u64;
+            progress.schedule.drain_before(&self.schedule, frame + 1, |param| match param {
+                StretchParam::TimeRatio(ratio) => self.set_time_ratio(ratio),
+                StretchParam::PitchScale(scale) => self.set_pitch_scale(scale),
+            });
+            unsafe {
+                self.apply_pending(progress.stage)?;
+            }
+
+            let end = match progress.schedule.next_frame() {
+                Some(next) if next < progress.frame + length as u64 => (next - progress.frame) as usize,
+                _ => length,
+            };
+            let last = end == length;
+            let input_ptrs = &mut progress.input_ptrs;
+            for_each_chunk(end - start, last && is_final, |offset, len, chunk_is_final| unsafe {
+                point_at(input_ptrs, input, start + offset);
+                check_status(rubberband_shim_process(self.state, input_ptrs.as_ptr(), len, chunk_is_final as i32))
+            })?;
+            start = end;
+            if last {
+                break;
+            }
+        }
+
+        progress.frame += length as u64;
+        self.schedule.set_frame(progress.frame);
+        progress.stage = if is_final { Stage::Finished } else { Stage::Processing };
+        Ok(())
+    }
+
+    /// Set a key-frame map to enforce a particular stretch profile (offline mode only).
+    ///
+    /// Each `(source, target)` pair maps a sample frame in the input to the sample frame in the
+    /// output at which it should land. The stretch is interpolated linearly between key frames.
+    /// The map only affects points within the material; the overall duration is still set by
+    /// the [time ratio](Self::set_time_ratio()), which should be set before calling this method.
+    ///
+    /// The map must be set before the first [process()](Self::process()) call, and is cleared by
+    /// [reset()](Self::reset()). The source frames are checked against the input duration, so it
+    /// must be known first: call [set_expected_input_duration()](Self::set_expected_input_duration())
+    /// or make the final [study()](Self::study()) call before this method.
+    ///
+    /// # Arguments
+    ///
+    /// * `map`: The `(source, target)` key frames, with both source and target frames strictly
+    ///   increasing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - A key frame is not strictly after the previous one, its source frame is beyond the input
+    ///   duration, or the input duration is not known yet ([`InvalidKeyFrameMap`](RubberBandError::InvalidKeyFrameMap)).
+    /// - Processing has already started ([`AlreadyProcessing`](RubberBandError::AlreadyProcessing)).
+    /// - The stretcher is in real-time mode ([`OfflineOnly`](RubberBandError::OfflineOnly)).
+    /// - A concurrent processing call is in progress ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::StretcherBuilder;
+    ///
+    /// let stretcher = StretcherBuilder::new(48000, 1)
+    ///     .unwrap()
+    ///     .time_ratio(1.5)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let input = vec![0.1f32; 48000];
+    /// stretcher.study(&[&input], true).unwrap();
+    ///
+    /// // Make the cut at 0.5 s land exactly at 1.0 s in the output
+    /// stretcher.set_key_frame_map(&[(24000, 48000)]).unwrap();
+    ///
+    /// stretcher.process(&[&input], true).unwrap();
+    /// ```
+    pub fn set_key_frame_map(&self, map: &[(usize, usize)]) -> Result<(), RubberBandError> {
+        if self.process_mode != StretcherProcessMode::Offline {
+            return Err(RubberBandError::OfflineOnly("set_key_frame_map"));
+        }
+        let progress = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        match progress.stage {
+            Stage::Idle | Stage::Studying | Stage::Studied => {}
+            Stage::Processing | Stage::Finished => {
+                return Err(RubberBandError::AlreadyProcessing("set_key_frame_map"));
+            }
+        }
+
+        let duration = progress.known_duration();
+
+        let mut sources: Vec<u32> = Vec::with_capacity(map.len());
+        let mut targets: Vec<u32> = Vec::with_capacity(map.len());
+        for (index, &(source, target)) in map.iter().enumerate() {
+            if index > 0 {
+                let (prev_source, prev_target) = map[index - 1];
+                if source <= prev_source || target <= prev_target {
+                    return Err(RubberBandError::InvalidKeyFrameMap {
+                        index,
+                        reason: "key frames must be strictly increasing",
+                    });
+                }
+            }
+            match duration {
+                None => {
+                    return Err(RubberBandError::InvalidKeyFrameMap {
+                        index,
+                        reason: "input duration unknown",
+                    });
+                }
+                Some(duration) if source >= duration => {
+                    return Err(RubberBandError::InvalidKeyFrameMap {
+                        index,
+                        reason: "source frame is beyond the input duration",
+                    });
+                }
+                Some(_) => {}
+            }
+            match (u32::try_from(source), u32::try_from(target)) {
+                (Ok(source), Ok(target)) => {
+                    sources.push(source);
+                    targets.push(target);
+                }
+                _ => {
+                    return Err(RubberBandError::InvalidKeyFrameMap {
+                        index,
+                        reason: "frame number is too large",
+                    });
+                }
+            }
+        }
+
+        unsafe {
+            // The map should be set after the ratios
+            self.apply_pending(progress.stage)?;
+            check_status(rubberband_shim_set_key_frame_map(
+                self.state,
+                map.len() as u32,
+                sources.as_mut_ptr(),
+                targets.as_mut_ptr(),
+            ))
+        }
+    }
+
+    /// Tell the [Stretcher] exactly how many input samples (per channel) it will receive (offline
+    /// mode only).
+    ///
+    /// This lets the stretcher make the output duration exactly the input duration times the time
+    /// ratio. It must be called before the first [study()](Self::study()) or
+    /// [process()](Self::process()) call of each input, and again after [reset()](Self::reset()).
+    ///
+    /// # Arguments
+    ///
+    /// * `samples`: The exact input duration in samples per channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - Studying or processing has already started ([`AlreadyProcessing`](RubberBandError::AlreadyProcessing)).
+    /// - The duration does not fit the C API's `unsigned int` ([`TooManySamples`](RubberBandError::TooManySamples)).
+    /// - The stretcher is in real-time mode, where no duration can be guaranteed ([`OfflineOnly`](RubberBandError::OfflineOnly)).
+    /// - A concurrent processing call is in progress ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::StretcherBuilder;
+    ///
+    /// let stretcher = StretcherBuilder::new(44100, 1)
+    ///     .unwrap()
+    ///     .time_ratio(1.5)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let input = vec![0.1f32; 44100];
+    /// stretcher.set_expected_input_duration(input.len()).unwrap();
+    /// stretcher.study(&[&input], true).unwrap();
+    /// stretcher.process(&[&input], true).unwrap();
+    /// ```
+    pub fn set_expected_input_duration(&self, samples: usize) -> Result<(), RubberBandError> {
+        if self.process_mode != StretcherProcessMode::Offline {
+            return Err(RubberBandError::OfflineOnly("set_expected_input_duration"));
+        }
+        let mut progress = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        if progress.stage != Stage::Idle {
+            return Err(RubberBandError::AlreadyProcessing("set_expected_input_duration"));
+        }
+        let c_samples = u32::try_from(samples).map_err(|_| RubberBandError::TooManySamples {
+            limit: u32::MAX as usize,
+            actual: samples,
+        })?;
+
+        check_status(unsafe { rubberband_shim_set_expected_input_duration(self.state, c_samples) })?;
+        progress.expected_duration = Some(samples);
+        Ok(())
+    }
+
+    /// Tell the [Stretcher] the largest number of samples (per channel) that will be passed to a
+    /// single [process()](Self::process()) call.
+    ///
+    /// In real-time mode, the stretcher otherwise expects each block to be no larger than
+    /// [samples_required()](Self::samples_required()). Setting this lets the C++ library size its
+    /// buffers up front, and `process` will then reject larger blocks with
+    /// [`TooManySamples`](RubberBandError::TooManySamples) instead of letting the library grow its
+    /// buffers on the audio thread. The wrapper itself does not allocate in `process`. The
+    /// setting is retained across [reset()](Self::reset()).
+    ///
+    /// This method may allocate, so it should not be called from a realtime context.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples`: The maximum block size in samples per channel. Must not exceed
+    ///   [process_size_limit()](Self::process_size_limit()).
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The size exceeds the process size limit ([`TooManySamples`](RubberBandError::TooManySamples)).
+    /// - A concurrent processing call is in progress ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::{StretcherBuilder, StretcherProcessMode, RubberBandError};
+    ///
+    /// let stretcher = StretcherBuilder::new(44100, 1)
+    ///     .unwrap()
+    ///     .process_mode(StretcherProcessMode::RealTime)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// stretcher.set_max_process_size(1024).unwrap();
+    ///
+    /// let input = vec![0.0f32; 2048];
+    /// assert!(matches!(
+    ///     stretcher.process(&[&input], false),
+    ///     Err(RubberBandError::TooManySamples { limit: 1024, actual: 2048 })
+    /// ));
+    /// ```
+    pub fn set_max_process_size(&self, samples: usize) -> Result<(), RubberBandError> {
+        let mut progress = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        let limit = self.process_size_limit();
+        if samples > limit {
+            return Err(RubberBandError::TooManySamples {
+                limit,
+                actual: samples,
+            });
+        }
+
+        check_status(unsafe { rubberband_shim_set_max_process_size(self.state, samples as u32) })?;
+        progress.max_process_size = Some(samples);
+        Ok(())
+    }
+
+    /// Get the hard upper limit on the number of samples (per channel) for a single
+    /// [process()](Self::process()) call.
+    ///
+    /// This is the largest value accepted by [set_max_process_size()](Self::set_max_process_size()).
+    /// In real-time mode, it is also the largest block `process` accepts if no maximum was set.
+    ///
+    /// # Returns
+    ///
+    /// The process size limit in samples per channel.
+    pub fn process_size_limit(&self) -> usize {
+        unsafe {
+            rubberband_get_process_size_limit(self.state) as usize
+        }
+    }
+
+    /// Get the number of output samples (per channel) ready to be retrieved.
+    ///
+    /// **Note:** This method acquires the internal processing lock and will block while another
+    /// processing call is in progress on the same instance.
+    ///
+    /// # Returns
+    ///
+    /// `Some(n)` with the number of samples available, or `None` once the final block has been
+    /// processed and all of the output has been retrieved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if the C++ library failed, as it may process pending input here
+    /// when it has no processing thread ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    /// [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn available(&self) -> Result<Option<usize>, RubberBandError> {
+        let _guard = self.mutex.lock();
+        let available = unsafe { self.available_locked()? };
+        Ok(usize::try_from(available).ok())
+    }
+
+    /// Get the number of output samples available, or a negative number once all of the output
+    /// has been retrieved.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the processing lock.
+    unsafe fn available_locked(&self) -> Result<i32, RubberBandError> {
+        let mut available = 0;
+        check_status(rubberband_shim_available(self.state, &mut available))?;
+        Ok(available)
+    }
+
+    /// Retrieve processed output samples into pre-allocated buffers.
+    ///
+    /// Retrieves at most as many samples as fit in the output buffers, and at most as many as are
+    /// [available](Self::available()).
+    ///
+    /// This performs no heap allocation, so it can be called from an audio thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `output`: A mutable slice of mutable slices (`&mut [&mut [f32]]`) for the output.
+    ///   - Must have `channel_count` inner slices.
+    ///   - All inner slices must have the same length.
+    ///
+    /// # Returns
+    ///
+    /// The number of samples (per channel) written to the start of each output slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - Output channel count or block size is inconsistent ([`InconsistentChannelCount`](RubberBandError::InconsistentChannelCount), [`InconsistentBlockSize`](RubberBandError::InconsistentBlockSize)).
+    /// - A concurrent processing call is in progress ([`OperationInProgress`](RubberBandError::OperationInProgress)).
+    /// - The C++ library failed ([`OutOfMemory`](RubberBandError::OutOfMemory),
+    ///   [`InvalidArgument`](RubberBandError::InvalidArgument), [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn retrieve_into(&self, output: &mut [&mut [f32]]) -> Result<usize, RubberBandError> {
+        let mut progress = self.mutex.try_lock().ok_or(RubberBandError::OperationInProgress)?;
+
+        let channel_count = self.channel_count() as usize;
+        if output.len() != channel_count {
+            return Err(RubberBandError::InconsistentChannelCount {
+                expected: channel_count,
+                actual: output.len(),
+            });
+        }
+        let capacity = output[0].len();
+        for (ch, slice) in output.iter().enumerate() {
+            if slice.len() != capacity {
+                return Err(RubberBandError::InconsistentBlockSize {
+                    channel: ch,
+                    expected: capacity,
+                    actual: slice.len(),
+                });
+            }
+        }
+
+        for (ptr, slice) in progress.output_ptrs.iter_mut().zip(output.iter_mut()) {
+            *ptr = slice.as_mut_ptr();
+        }
+
+        let mut retrieved = 0;
+        unsafe {
+            let available = self.available_locked()?.max(0) as usize;
+            let samples = available.min(capacity).min(u32::MAX as usize) as u32;
+            check_status(rubberband_shim_retrieve(self.state, progress.output_ptrs.as_ptr(), samples, &mut retrieved))?;
+        }
+        Ok(retrieved as usize)
+    }
+
+    /// Retrieve all currently available output samples, allocating and returning them.
+    ///
+    /// This is a convenience wrapper around [retrieve_into()](Self::retrieve_into()).
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<Vec<f32>>` with one inner vector per channel, each holding the retrieved samples.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if a concurrent processing call is in progress
+    /// ([`OperationInProgress`](RubberBandError::OperationInProgress)), or if the C++ library
+    /// failed, as for [retrieve_into()](Self::retrieve_into()).
+    pub fn retrieve(&self) -> Result<Vec<Vec<f32>>, RubberBandError> {
+        let available = self.available()?.unwrap_or(0);
+        let mut output = vec![vec![0.0; available]; self.channel_count() as usize];
+        let mut output_slices: Vec<&mut [f32]> = output
+            .iter_mut()
+            .map(|slice| slice.as_mut_slice())
+            .collect();
+        let retrieved = self.retrieve_into(&mut output_slices)?;
+        for channel in output.iter_mut() {
+            channel.truncate(retrieved);
+        }
+        Ok(output)
+    }
+
+    /// Schedule a time ratio change at the given input frame (real-time mode only).
+    ///
+    /// Frames are counted in input samples (per channel) passed to [process()](Self::process())
+    /// since the last [reset()](Self::reset()) (or since creation), see
+    /// [frame_position()](Self::frame_position()). The `process` call containing `frame` is split
+    /// there, so the change takes effect exactly at that frame, as if
+    /// [set_time_ratio()](Self::set_time_ratio()) were called between two blocks ending and
+    /// starting there. If the frame has already been processed, the change is applied at the
+    /// start of the next `process` call instead. The returned [ScheduledChange] reports the frame
+    /// at which the change actually takes effect.
+    ///
+    /// This method is lock-free and does not allocate, so it may be called from any thread while
+    /// processing; the queue capacity is set by [StretcherBuilder::schedule_capacity()]. A change
+    /// scheduled within the `process` call in progress at the same time is applied at the start
+    /// of the next one, which the returned value may not reflect.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The input frame at which the change should take effect.
+    /// * `ratio`: The desired time ratio.
+    ///
+    /// # Returns
+    ///
+    /// Where the change was scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The time ratio is not a finite positive number ([`InvalidTimeRatio`](RubberBandError::InvalidTimeRatio)).
+    /// - The stretcher is in offline mode, where the ratio cannot change during processing ([`RealTimeOnly`](RubberBandError::RealTimeOnly)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubberband::{StretcherBuilder, StretcherProcessMode};
+    ///
+    /// let stretcher = StretcherBuilder::new(44100, 1)
+    ///     .unwrap()
+    ///     .process_mode(StretcherProcessMode::RealTime)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Slow down to 80% speed one second in
+    /// let change = stretcher.schedule_time_ratio(44100, 1.25).unwrap();
+    /// assert_eq!(change.applied, 44100);
+    /// ```
+    pub fn schedule_time_ratio(&self, frame: u64, ratio: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_time_ratio(ratio)?;
+        self.schedule(frame, StretchParam::TimeRatio(ratio), "schedule_time_ratio")
+    }
+
+    /// Schedule a pitch scale change at the given input frame (real-time mode only).
+    ///
+    /// Scheduling works as for [schedule_time_ratio()](Self::schedule_time_ratio()), with each
+    /// change applied as if [set_pitch_scale()](Self::set_pitch_scale()) were called at that frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: The input frame at which the change should take effect.
+    /// * `scale`: The desired pitch scale (ratio).
+    ///
+    /// # Returns
+    ///
+    /// Where the change was scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if:
+    /// - The pitch scale is not a finite positive number ([`InvalidPitchScale`](RubberBandError::InvalidPitchScale)).
+    /// - The stretcher is in offline mode, where the scale cannot change during processing ([`RealTimeOnly`](RubberBandError::RealTimeOnly)).
+    /// - The queue of pending changes is full ([`ScheduleFull`](RubberBandError::ScheduleFull)).
+    pub fn schedule_pitch_scale(&self, frame: u64, scale: f64) -> Result<ScheduledChange, RubberBandError> {
+        check_pitch_scale(scale)?;
+        self.schedule(frame, StretchParam::PitchScale(scale), "schedule_pitch_scale")
+    }
+
+    /// Get the number of input frames processed since the last [reset()](Self::reset()).
+    ///
+    /// This is the frame counter that scheduled changes are measured against.
+    ///
+    /// **Note:** This method acquires the internal processing lock and will block while another
+    /// processing call is in progress on the same instance.
+    pub fn frame_position(&self) -> u64 {
+        self.mutex.lock().frame
+    }
+
+    /// Reset the internal state of the [Stretcher].
+    ///
+    /// This clears the internal buffers, the study data, the key-frame map and the expected input
+    /// duration, so that a new input can be studied and processed from the beginning, retaining
+    /// the time ratio, pitch scale and maximum process size. The frame counter is set back to 0,
+    /// and pending scheduled changes are discarded. In offline mode,
+    /// time ratio and pitch scale changes made since processing started take effect after this.
+    ///
+    /// **Note:** This method acquires the internal processing lock and will block while another
+    /// processing call is in progress on the same instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns [RubberBandError] if the C++ library failed to reset
+    /// ([`OutOfMemory`](RubberBandError::OutOfMemory), [`InvalidArgument`](RubberBandError::InvalidArgument),
+    /// [`LibraryException`](RubberBandError::LibraryException)).
+    pub fn reset(&self) -> Result<(), RubberBandError> {
+        let mut progress = self.mutex.lock();
+        check_status(unsafe { rubberband_shim_reset(self.state) })?;
+        progress.stage = Stage::Idle;
+        progress.studied = 0;
+        progress.expected_duration = None;
+        progress.frame = 0;
+        progress.schedule.clear(&self.schedule);
+        self.schedule.set_frame(0);
+        Ok(())
+    }
+
+    /// Schedule a parameter change at `frame`, for the `schedule_*` method named `method`.
+    fn schedule(&self, frame: u64, param: StretchParam, method: &'static str) -> Result<ScheduledChange, RubberBandError> {
+        if self.process_mode != StretcherProcessMode::RealTime {
+            return Err(RubberBandError::RealTimeOnly(method));
+        }
+        self.schedule.push(frame, param)?;
+        Ok(ScheduledChange {
+            requested: frame,
+            applied: frame.max(self.schedule.frame()),
+        })
+    }
+
+    /// Pass pending option, time ratio and pitch scale changes to the C++ instance.
+    ///
+    /// In offline mode, the ratios cannot change once studying or processing has started, so
+    /// pending ratio changes are held until the stretcher is reset. A change the C++ library fails
+    /// to apply stays pending, so that it is retried by the next call instead of lost.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the processing lock, and pass the stage it guards.
+    unsafe fn apply_pending(&self, stage: Stage) -> Result<(), RubberBandError> {
+        if let Some(bits) = self.transients_option.take() {
+            check_status(rubberband_shim_set_transients_option(self.state, bits)).inspect_err(|_| self.transients_option.retry())?;
+        }
+        if let Some(bits) = self.detector_option.take() {
+            check_status(rubberband_shim_set_detector_option(self.state, bits)).inspect_err(|_| self.detector_option.retry())?;
+        }
+        if let Some(bits) = self.phase_option.take() {
+            check_status(rubberband_shim_set_phase_option(self.state, bits)).inspect_err(|_| self.phase_option.retry())?;
+        }
+        if let Some(bits) = self.pitch_option.take() {
+            check_status(rubberband_shim_set_pitch_option(self.state, bits)).inspect_err(|_| self.pitch_option.retry())?;
+        }
+
+        if self.process_mode == StretcherProcessMode::Offline && stage != Stage::Idle {
+            return Ok(());
+        }
+        if self.time_dirty.swap(false, Ordering::Acquire) {
+            check_status(rubberband_shim_set_time_ratio(self.state, self.time_ratio.load(Ordering::Relaxed)))
+                .inspect_err(|_| self.time_dirty.store(true, Ordering::Release))?;
+        }
+        if self.pitch_dirty.swap(false, Ordering::Acquire) {
+            check_status(rubberband_shim_set_pitch_scale(self.state, self.pitch_scale.load(Ordering::Relaxed)))
+                .inspect_err(|_| self.pitch_dirty.store(true, Ordering::Release))?;
+        }
+        Ok(())
+    }
+
+    /// Check that the input buffers match the channel count and have equal lengths.
+    fn check_input(&self, input: &[&[f32]]) -> Result<(), RubberBandError> {
+        let channel_count = self.channel_count() as usize;
+        if input.len() != channel_count {
+            return Err(RubberBandError::InconsistentChannelCount {
+                expected: channel_count,
+                actual: input.len(),
+            });
+        }
+        let block_size = input[0].len();
+        for (ch, slice) in input.iter().enumerate() {
+            if slice.len() != block_size {
+                return Err(RubberBandError::InconsistentBlockSize {
+                    channel: ch,
+                    expected: block_size,
+                    actual: slice.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn threading_bits(option: StretcherThreading) -> RubberBandOption {
+    match option {
+        // Without threading support, the library never starts threads anyway
+        StretcherThreading::Auto if NO_THREADING => OPTION_BITS_THREADING_NEVER,
+        StretcherThreading::Auto => OPTION_BITS_THREADING_AUTO,
+        StretcherThreading::Never => OPTION_BITS_THREADING_NEVER,
+        StretcherThreading::Always => OPTION_BITS_THREADING_ALWAYS,
+    }
+}
+
+fn transients_bits(option: StretcherTransients) -> RubberBandOption {
+    match option {
+        StretcherTransients::Crisp => OPTION_BITS_TRANSIENTS_CRISP,
+        StretcherTransients::Mixed => OPTION_BITS_TRANSIENTS_MIXED,
+        StretcherTransients::Smooth => OPTION_BITS_TRANSIENTS_SMOOTH,
+    }
+}
+
+fn detector_bits(option: StretcherDetector) -> RubberBandOption {
+    match option {
+        StretcherDetector::Compound => OPTION_BITS_DETECTOR_COMPOUND,
+        StretcherDetector::Percussive => OPTION_BITS_DETECTOR_PERCUSSIVE,
+        StretcherDetector::Soft => OPTION_BITS_DETECTOR_SOFT,
+    }
+}
+
+fn phase_bits(option: StretcherPhase) -> RubberBandOption {
+    match option {
+        StretcherPhase::Laminar => OPTION_BITS_PHASE_LAMINAR,
+        StretcherPhase::Independent => OPTION_BITS_PHASE_INDEPENDENT,
+    }
+}
+
+fn pitch_mode_bits(option: StretcherPitchMode) -> RubberBandOption {
+    match option {
+        StretcherPitchMode::HighSpeed => OPTION_BITS_PITCH_HIGH_SPEED,
+        StretcherPitchMode::HighQuality => OPTION_BITS_PITCH_HIGH_QUALITY,
+        StretcherPitchMode::HighConsistency => OPTION_BITS_PITCH_HIGH_CONSISTENCY,
+    }
+}
+
+/// Check that the time ratio is a finite number greater than 0.
+fn check_time_ratio(ratio: f64) -> Result<(), RubberBandError> {
+    if !ratio.is_finite() || ratio <= 0.0 {
+        return Err(RubberBandError::InvalidTimeRatio(ratio));
+    }
+    Ok(())
+}
+
+/// Point each of `ptrs` at the sample at `offset` in the matching channel of `input`.
+///
+/// # Safety
+///
+/// `offset` must be within the bounds of every channel, or equal to their length.
+unsafe fn point_at(ptrs: &mut [*const f32], input: &[&[f32]], offset: usize) {
+    for (ptr, slice) in ptrs.iter_mut().zip(input) {
+        *ptr = slice.as_ptr().add(offset);
+    }
+}
+
+/// Split `len` samples into chunks the C API can take (sample counts are `unsigned int`), calling
+/// `f(offset, chunk_len, chunk_is_final)` for each. Only the last chunk inherits `is_final`.
+fn for_each_chunk<F>(len: usize, is_final: bool, mut f: F) -> Result<(), RubberBandError>
+where F: FnMut(usize, u32, bool) -> Result<(), RubberBandError>
+{
+    let max_chunk = u32::MAX as usize;
+    let mut offset = 0;
+    loop {
+        let chunk = (len - offset).min(max_chunk);
+        let last = offset + chunk == len;
+        f(offset, chunk as u32, last && is_final)?;
+        offset += chunk;
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+impl Drop for Stretcher {
+    fn drop(&mut self) {
+        unsafe { rubberband_delete(self.state) };
+    }
+}
+
+unsafe impl Send for Stretcher {}
+unsafe impl Sync for Stretcher {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a whole mono signal through the offline study / process workflow.
+    fn stretch_offline(stretcher: &Stretcher, input: &[f32]) -> Vec<f32> {
+        stretcher.study(&[input], true).unwrap();
+        stretcher.process(&[input], true).unwrap();
+        retrieve_all(stretcher)
+    }
+
+    /// Retrieve the output of a mono offline stretcher until it is finished, waiting for any
+    /// output still being processed by its threads.
+    fn retrieve_all(stretcher: &Stretcher) -> Vec<f32> {
+        let mut output = Vec::new();
+        while let Some(available) = stretcher.available().unwrap() {
+            if available == 0 {
+                std::thread::yield_now();
+                continue;
+            }
+            let block = stretcher.retrieve().unwrap();
+            output.extend_from_slice(&block[0]);
+        }
+        output
+    }
+
+    #[test]
+    fn test_builder_invalid_params() {
+        assert!(StretcherBuilder::new(0, 2).is_err());
+        assert!(StretcherBuilder::new(44100, 0).is_err());
+
+        for ratio in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                StretcherBuilder::new(44100, 1).unwrap().time_ratio(ratio).build(),
+                Err(RubberBandError::InvalidTimeRatio(_))
+            ));
+            assert!(matches!(
+                StretcherBuilder::new(44100, 1).unwrap().pitch_scale(ratio).build(),
+                Err(RubberBandError::InvalidPitchScale(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_builder_ratios() {
+        let stretcher = StretcherBuilder::new(44100, 2)
+            .unwrap()
+            .time_ratio(1.5)
+            .pitch_scale(0.75)
+            .build()
+            .unwrap();
+        assert_eq!(stretcher.channel_count(), 2);
+        assert_eq!(stretcher.time_ratio(), 1.5);
+        assert_eq!(stretcher.pitch_scale(), 0.75);
+    }
+
+    #[test]
+    fn test_try_setters() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .build()
+            .unwrap();
+
+        for ratio in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(stretcher.try_set_time_ratio(ratio), Err(RubberBandError::InvalidTimeRatio(_))));
+            assert!(matches!(stretcher.try_set_pitch_scale(ratio), Err(RubberBandError::InvalidPitchScale(_))));
+        }
+        assert_eq!(stretcher.time_ratio(), 1.0);
+        assert_eq!(stretcher.pitch_scale(), 1.0);
+
+        stretcher.try_set_time_ratio(1.5).unwrap();
+        stretcher.try_set_pitch_scale(0.75).unwrap();
+        assert_eq!(stretcher.time_ratio(), 1.5);
+        assert_eq!(stretcher.pitch_scale(), 0.75);
+        assert!(stretcher.samples_required() > 0);
+    }
+
+    #[test]
+    fn test_deferred_option_retry() {
+        let option = DeferredOption::new();
+        assert_eq!(option.take(), None);
+
+        option.store(OPTION_BITS_PHASE_INDEPENDENT);
+        let bits = option.take();
+        assert_eq!(bits, Some(OPTION_BITS_PHASE_INDEPENDENT as RubberBandOptions));
+        assert_eq!(option.take(), None);
+
+        // A failed change is taken again by the next call
+        option.retry();
+        assert_eq!(option.take(), bits);
+    }
+
+    #[test]
+    fn test_builder_engine_option() {
+        for (engine, version) in [(StretcherEngine::Faster, 2), (StretcherEngine::Finer, 3)] {
+            for mode in [StretcherProcessMode::Offline, StretcherProcessMode::RealTime] {
+                let stretcher = StretcherBuilder::new(44100, 1)
+                    .unwrap()
+                    .process_mode(mode)
+                    .engine(engine)
+                    .build()
+                    .unwrap();
+                assert_eq!(stretcher.engine_version(), version);
+                assert_eq!(stretcher.engine(), engine);
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder_r2_options_with_finer_engine() {
+        let builder = || StretcherBuilder::new(44100, 1).unwrap().engine(StretcherEngine::Finer);
+
+        // The defaults are fine, since they do not ask for anything R3 would ignore
+        assert!(builder().build().is_ok());
+
+        assert!(matches!(
+            builder().transients(StretcherTransients::Smooth).build(),
+            Err(RubberBandError::UnsupportedByEngine { option: "transients", .. })
+        ));
+        assert!(matches!(
+            builder().detector(StretcherDetector::Soft).build(),
+            Err(RubberBandError::UnsupportedByEngine { option: "detector", .. })
+        ));
+        assert!(matches!(
+            builder().phase(StretcherPhase::Independent).build(),
+            Err(RubberBandError::UnsupportedByEngine { option: "phase", .. })
+        ));
+    }
+
+    #[test]
+    fn test_runtime_r2_options() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .transients(StretcherTransients::Mixed)
+            .detector(StretcherDetector::Percussive)
+            .build()
+            .unwrap();
+        let input = vec![0.5f32; 1024];
+
+        for (transients, detector, phase) in [
+            (StretcherTransients::Crisp, StretcherDetector::Compound, StretcherPhase::Laminar),
+            (StretcherTransients::Smooth, StretcherDetector::Soft, StretcherPhase::Independent),
+        ] {
+            assert!(stretcher.set_transients_option(transients).is_ok());
+            assert!(stretcher.set_detector_option(detector).is_ok());
+            assert!(stretcher.set_phase_option(phase).is_ok());
+            assert!(stretcher.process(&[&input], false).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_runtime_r2_options_in_offline_mode() {
+        let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+
+        assert!(matches!(
+            stretcher.set_transients_option(StretcherTransients::Smooth),
+            Err(RubberBandError::RealTimeOnly(_))
+        ));
+        assert!(matches!(
+            stretcher.set_detector_option(StretcherDetector::Soft),
+            Err(RubberBandError::RealTimeOnly(_))
+        ));
+        assert!(stretcher.set_phase_option(StretcherPhase::Independent).is_ok());
+    }
+
+    #[test]
+    fn test_runtime_r2_options_with_finer_engine() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .engine(StretcherEngine::Finer)
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            stretcher.set_transients_option(StretcherTransients::Smooth),
+            Err(RubberBandError::UnsupportedByEngine { .. })
+        ));
+        assert!(matches!(
+            stretcher.set_detector_option(StretcherDetector::Soft),
+            Err(RubberBandError::UnsupportedByEngine { .. })
+        ));
+        assert!(matches!(
+            stretcher.set_phase_option(StretcherPhase::Independent),
+            Err(RubberBandError::UnsupportedByEngine { .. })
+        ));
+    }
+
+    #[test]
+    fn test_builder_pitch_mode() {
+        // HighConsistency is real-time only
+        assert!(matches!(
+            StretcherBuilder::new(44100, 1)
+                .unwrap()
+                .pitch_mode(StretcherPitchMode::HighConsistency)
+                .build(),
+            Err(RubberBandError::RealTimeOnly(_))
+        ));
+        assert!(StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .pitch_mode(StretcherPitchMode::HighConsistency)
+            .build()
+            .is_ok());
+
+        // The other modes work in both processing modes
+        for mode in [StretcherProcessMode::Offline, StretcherProcessMode::RealTime] {
+            for pitch_mode in [StretcherPitchMode::HighSpeed, StretcherPitchMode::HighQuality] {
+                assert!(StretcherBuilder::new(44100, 1)
+                    .unwrap()
+                    .process_mode(mode)
+                    .pitch_mode(pitch_mode)
+                    .build()
+                    .is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder_threading() {
+        let input = vec![0.1f32; 4096];
+        for threading in [StretcherThreading::Auto, StretcherThreading::Never] {
+            let stretcher = StretcherBuilder::new(44100, 2).unwrap().threading(threading).build().unwrap();
+            stretcher.study(&[&input, &input], true).unwrap();
+            stretcher.process(&[&input, &input], true).unwrap();
+        }
+
+        // Always is only accepted if the library was compiled with threading
+        let result = StretcherBuilder::new(44100, 2)
+            .unwrap()
+            .threading(StretcherThreading::Always)
+            .build();
+        if NO_THREADING {
+            assert!(matches!(result, Err(RubberBandError::ThreadingUnavailable)));
+        } else {
+            let stretcher = result.unwrap();
+            stretcher.study(&[&input, &input], true).unwrap();
+            stretcher.process(&[&input, &input], true).unwrap();
+            assert!(stretcher.available().unwrap().unwrap() > 0);
+        }
+        assert_eq!(threading_bits(StretcherThreading::Auto) == OPTION_BITS_THREADING_NEVER, NO_THREADING);
+    }
+
+    #[test]
+    fn test_runtime_pitch_option() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .build()
+            .unwrap();
+        let input = vec![0.5f32; 1024];
+
+        for option in [
+            StretcherPitchMode::HighConsistency,
+            StretcherPitchMode::HighQuality,
+            StretcherPitchMode::HighSpeed,
+        ] {
+            assert!(stretcher.set_pitch_option(option).is_ok());
+            stretcher.set_pitch_scale(1.2);
+            assert!(stretcher.process(&[&input], false).is_ok());
+        }
+
+        // Offline mode fixes the pitch option on construction
+        let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+        assert!(matches!(
+            stretcher.set_pitch_option(StretcherPitchMode::HighQuality),
+            Err(RubberBandError::RealTimeOnly(_))
+        ));
+    }
+
+    #[test]
+    fn test_study_after_process() {
+        let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+        let input = vec![0.5f32; 1024];
+
+        stretcher.process(&[&input], false).unwrap();
+        assert!(matches!(
+            stretcher.study(&[&input], true),
+            Err(RubberBandError::AlreadyProcessing(_))
+        ));
+    }
+
+    #[test]
+    fn test_process_before_final_study() {
+        let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+        let input = vec![0.5f32; 1024];
+
+        stretcher.study(&[&input], false).unwrap();
+        assert!(matches!(
+            stretcher.process(&[&input], true),
+            Err(RubberBandError::StudyIncomplete)
+        ));
+    }
+
+    #[test]
+    fn test_process_after_final() {
+        let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+        let input = vec![0.5f32; 1024];
+
+        stretcher.process(&[&input], true).unwrap();
+        assert!(matches!(
+            stretcher.process(&[&input], true),
+            Err(RubberBandError::AlreadyFinished)
+        ));
+
+        // Reset allows a new input to be processed
+        stretcher.reset().unwrap();
+        assert!(stretcher.process(&[&input], true).is_ok());
+    }
+
+    #[test]
+    fn test_process_invalid_channels() {
+        let stretcher = StretcherBuilder::new(44100, 2).unwrap().build().unwrap();
+        let input = vec![0.0f32; 1024];
+
+        assert!(matches!(
+            stretcher.process(&[&input], true),
+            Err(RubberBandError::InconsistentChannelCount { .. })
+        ));
+    }
+
+    #[test]
+    fn test_process_inconsistent_lengths() {
+        let stretcher = StretcherBuilder::new(44100, 2).unwrap().build().unwrap();
+        let left = vec![0.0f32; 1024];
+        let right = vec![0.0f32; 1000];
+
+        assert!(matches!(
+            stretcher.process(&[&left, &right], true),
+            Err(RubberBandError::InconsistentBlockSize { channel: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_key_frame_map_validation() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .time_ratio(2.0)
+            .build()
+            .unwrap();
+        let input = vec![0.5f32; 44100];
+
+        // Not checkable before the input duration is known
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(1000, 2000)]),
+            Err(RubberBandError::InvalidKeyFrameMap { index: 0, reason: "input duration unknown" })
+        ));
+        stretcher.study(&[&input[..22050]], false).unwrap();
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(1000, 2000)]),
+            Err(RubberBandError::InvalidKeyFrameMap { index: 0, reason: "input duration unknown" })
+        ));
+        stretcher.study(&[&input[22050..]], true).unwrap();
+
+        // Not monotonic
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(1000, 2000), (1000, 3000)]),
+            Err(RubberBandError::InvalidKeyFrameMap { index: 1, .. })
+        ));
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(1000, 2000), (2000, 1500)]),
+            Err(RubberBandError::InvalidKeyFrameMap { index: 1, .. })
+        ));
+
+        // Beyond the studied input duration
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(1000, 2000), (44100, 88200)]),
+            Err(RubberBandError::InvalidKeyFrameMap { index: 1, .. })
+        ));
+
+        // Valid map
+        assert!(stretcher.set_key_frame_map(&[(0, 0), (11025, 30000), (22050, 44100)]).is_ok());
+
+        // Not allowed once processing has started
+        stretcher.process(&[&input], false).unwrap();
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(0, 0)]),
+            Err(RubberBandError::AlreadyProcessing(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_frame_map_in_realtime() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(0, 0)]),
+            Err(RubberBandError::OfflineOnly(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_frame_map_output_duration() {
+        let sample_rate = 44100;
+        let input: Vec<f32> = (0..sample_rate).map(|n| (n as f32 * 0.05).sin()).collect();
+        let stretcher = StretcherBuilder::new(sample_rate as u32, 1)
+            .unwrap()
+            .time_ratio(2.0)
+            .build()
+            .unwrap();
+
+        // The key frames change the stretch profile, but not the overall duration
+        stretcher.study(&[&input], true).unwrap();
+        stretcher.set_key_frame_map(&[(11025, 44100), (33075, 66150)]).unwrap();
+        stretcher.process(&[&input], true).unwrap();
+        let output = retrieve_all(&stretcher);
+
+        assert!(output.len().abs_diff(input.len() * 2) <= 1, "Output length mismatch: {}", output.len());
+    }
+
+    #[test]
+    fn test_expected_input_duration() {
+        let input: Vec<f32> = (0..44100).map(|n| (n as f32 * 0.05).sin()).collect();
+
+        for ratio in [0.75, 1.3, 2.0] {
+            let stretcher = StretcherBuilder::new(44100, 1)
+                .unwrap()
+                .time_ratio(ratio)
+                .build()
+                .unwrap();
+            stretcher.set_expected_input_duration(input.len()).unwrap();
+            let output = stretch_offline(&stretcher, &input);
+
+            // With the exact duration known, the output length is exact up to rounding
+            let expected = (input.len() as f64 * ratio).round();
+            assert!(
+                (output.len() as f64 - expected).abs() <= 1.0,
+                "Output length mismatch for ratio {}: expected {}, got {}",
+                ratio, expected, output.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_expected_input_duration_validation() {
+        let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+        let input = vec![0.5f32; 1024];
+
+        // The key frames are checked against the expected duration
+        stretcher.set_expected_input_duration(input.len()).unwrap();
+        assert!(matches!(
+            stretcher.set_key_frame_map(&[(1024, 2048)]),
+            Err(RubberBandError::InvalidKeyFrameMap { index: 0, .. })
+        ));
+        assert!(stretcher.set_key_frame_map(&[(1023, 2048)]).is_ok());
+
+        stretcher.study(&[&input], true).unwrap();
+        assert!(matches!(
+            stretcher.set_expected_input_duration(input.len()),
+            Err(RubberBandError::AlreadyProcessing(_))
+        ));
+
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            stretcher.set_expected_input_duration(input.len()),
+            Err(RubberBandError::OfflineOnly(_))
+        ));
+    }
+
+    #[test]
+    fn test_max_process_size() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .build()
+            .unwrap();
+        let limit = stretcher.process_size_limit();
+        assert!(limit > 0);
+
+        assert!(matches!(
+            stretcher.set_max_process_size(limit + 1),
+            Err(RubberBandError::TooManySamples { .. })
+        ));
+
+        stretcher.set_max_process_size(1024).unwrap();
+        let input = vec![0.5f32; 2048];
+        assert!(matches!(
+            stretcher.process(&[&input], false),
+            Err(RubberBandError::TooManySamples { limit: 1024, actual: 2048 })
+        ));
+        assert!(stretcher.process(&[&input[..1024]], false).is_ok());
+
+        // The maximum is retained across reset
+        stretcher.reset().unwrap();
+        assert!(matches!(
+            stretcher.process(&[&input], false),
+            Err(RubberBandError::TooManySamples { limit: 1024, actual: 2048 })
+        ));
+    }
+
+    #[test]
+    fn test_study_in_realtime() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .build()
+            .unwrap();
+        let input = vec![0.5f32; 1024];
+
+        assert!(matches!(
+            stretcher.study(&[&input], true),
+            Err(RubberBandError::OfflineOnly(_))
+        ));
+    }
+
+    #[test]
+    fn test_realtime_ratio_change() {
+        let sample_rate = 48000;
+        let stretcher = StretcherBuilder::new(sample_rate, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .build()
+            .unwrap();
+
+        // Stream about 2 seconds of input at each ratio, counting the input and output samples
+        let mut output = vec![0.0f32; 4096];
+        for ratio in [1.0, 2.0, 0.5] {
+            stretcher.set_time_ratio(ratio);
+            assert_eq!(stretcher.time_ratio(), ratio);
+
+            let mut consumed = 0;
+            let mut produced = 0;
+            while consumed < 2 * sample_rate as usize {
+                let required = stretcher.samples_required().max(1);
+                let input = vec![0.1f32; required];
+                stretcher.process(&[&input], false).unwrap();
+                consumed += required;
+                while stretcher.available().unwrap().unwrap_or(0) > 0 {
+                    produced += stretcher.retrieve_into(&mut [&mut output]).unwrap();
+                }
+            }
+
+            // Allow for the latency and the transition from the previous ratio
+            let measured = produced as f64 / consumed as f64;
+            assert!(
+                (measured - ratio).abs() < 0.2 * ratio,
+                "Time ratio mismatch: expected {}, measured {}",
+                ratio, measured
+            );
+        }
+    }
+
+    #[test]
+    fn test_schedule_in_realtime() {
+        let stretcher = StretcherBuilder::new(44100, 1)
+            .unwrap()
+            .process_mode(StretcherProcessMode::RealTime)
+            .schedule_capacity(2)
+            .build()
+            .unwrap();
+        let input = vec![0.1f32; 512];
+
+        // Changes take effect at the requested frame, within the `process` call containing it
+        let change = stretcher.schedule_time_ratio(1000, 1.5).unwrap();
+        assert_eq!(change, ScheduledChange { requested: 1000, applied: 1000 });
+        stretcher.schedule_pitch_scale(1024, 0.8).unwrap();
+        assert!(matches!(stretcher.schedule_pitch_scale(2000, 1.2), Err(RubberBandError::ScheduleFull(2))));
+
+        stretcher.process(&[&input], false).unwrap();
+        assert_eq!(stretcher.frame_position(), 512);
+        assert_eq!(stretcher.time_ratio(), 1.0);
+        stretcher.process(&[&input], false).unwrap();
+        assert_eq!(stretcher.time_ratio(), 1.5);
+        assert_eq!(stretcher.pitch_scale(), 1.0);
+        stretcher.process(&[&input], false).unwrap();
+        assert_eq!(stretcher.pitch_scale(), 0.8);
+
+        // A frame already processed is applied at the next call
+        let change = stretcher.schedule_time_ratio(100, 2.0).unwrap();
+        assert_eq!(change.applied, 1536);
+        assert_eq!(change.quantization(), 1436);
+
+        // Resetting discards the pending changes and the frame counter
+        stretcher.reset().unwrap();
+        assert_eq!(stretcher.frame_position(), 0);
+        stretcher.process(&[&input], false).unwrap();
+        assert_eq!(stretcher.time_ratio(), 1.5);
+
+        assert!(matches!(stretcher.schedule_time_ratio(0, 0.0), Err(RubberBandError::InvalidTimeRatio(_))));
+        assert!(matches!(stretcher.schedule_pitch_scale(0, f64::NAN), Err(RubberBandError::InvalidPitchScale(_))));
+    }
+
+    #[test]
+    fn test_schedule_in_offline_mode() {
+        let stretcher = StretcherBuilder::new(44100, 1).unwrap().build().unwrap();
+        assert!(matches!(
+            stretcher.schedule_time_ratio(0, 1.5),
+            Err(RubberBandError::RealTimeOnly("schedule_time_ratio"))
+        ));
+        assert!(matches!(
+            stretcher.schedule_pitch_scale(0, 1.5),
+            Err(RubberBandError::RealTimeOnly("schedule_pitch_scale"))
+        ));
+    }
+
+    #[test]
+    fn test_output_duration() {
+        let sample_rate = 44100;
+        let input: Vec<f32> = (0..sample_rate).map(|n| (n as f32 * 0.05).sin()).collect();
+
+        for ratio in [0.5, 1.0, 2.0] {
+            let stretcher = StretcherBuilder::new(sample_rate as u32, 1)
+                .unwrap()
+                .time_ratio(ratio)
+                .build()
+                .unwrap();
+            let output = stretch_offline(&stretcher, &input);
+
+            // The study pass gives the exact input duration, so none of the tail is lost
+            let expected = (input.len() as f64 * ratio).round();
+            assert!(
+                (output.len() as f64 - expected).abs() <= 1.0,
+                "Output length mismatch for ratio {}: expected {}, got {}",
+                ratio, expected, output.len()
+            );
+            assert!(stretcher.available().unwrap().is_none());
+        }
+    }
+}
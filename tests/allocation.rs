@@ -0,0 +1,226 @@
+use rubberband::{
+    Dither, LiveShifterBuilder, LiveShifterFormant, LiveShifterN, LiveShifterStream, StretcherBuilder,
+    StretcherProcessMode,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// A global allocator that counts the heap allocations made by the current thread.
+///
+/// Only allocations made through the Rust global allocator are counted, i.e. those made by this
+/// crate. The C++ library manages its own memory, and is designed not to allocate while shifting.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    // `try_with` as the thread-local may already be destroyed when a thread exits
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Count the allocations made by the current thread while running `f`.
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(|count| count.get());
+    f();
+    ALLOCATIONS.with(|count| count.get()) - before
+}
+
+/// Test that `process_into` does not allocate, including when the pitch scale changes
+#[test]
+fn test_process_into_no_allocation() {
+    let shifter = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+    let block_size = shifter.block_size() as usize;
+
+    let input = [vec![0.5f32; block_size], vec![-0.5f32; block_size]];
+    let mut output = [vec![0.0f32; block_size], vec![0.0f32; block_size]];
+    let input_slices: [&[f32]; 2] = [&input[0], &input[1]];
+
+    let allocations = count_allocations(|| {
+        for i in 0..100 {
+            shifter.set_pitch_scale(1.0 + (i % 10) as f64 * 0.05);
+            let [out_ch1, out_ch2] = &mut output;
+            let mut output_slices: [&mut [f32]; 2] = [out_ch1, out_ch2];
+            shifter.process_into(&input_slices, &mut output_slices).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "process_into allocated {} times", allocations);
+}
+
+/// Test that `process_interleaved_into` does not allocate
+#[test]
+fn test_process_interleaved_into_no_allocation() {
+    let shifter = LiveShifterBuilder::new(48000, 2).unwrap().build().unwrap();
+    let block_size = shifter.block_size() as usize;
+
+    let input = vec![0.5f32; 2 * block_size];
+    let mut output = vec![0.0f32; 2 * block_size];
+
+    let allocations = count_allocations(|| {
+        for i in 0..100 {
+            shifter.set_pitch_scale(1.0 + (i % 10) as f64 * 0.05);
+            shifter.process_interleaved_into(&input, &mut output).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "process_interleaved_into allocated {} times", allocations);
+}
+
+/// Test that `process_in_place` does not allocate, on the shifter and on a split processor
+#[test]
+fn test_process_in_place_no_allocation() {
+    let shifter = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+    let block_size = shifter.block_size() as usize;
+    let mut left = vec![0.1f32; block_size];
+    let mut right = vec![-0.1f32; block_size];
+
+    let allocations = count_allocations(|| {
+        for _ in 0..100 {
+            shifter.process_in_place(&mut [&mut left, &mut right]).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "process_in_place allocated {} times", allocations);
+
+    let (mut processor, _controller) = shifter.split();
+    let allocations = count_allocations(|| {
+        for _ in 0..100 {
+            processor.process_in_place(&mut [&mut left, &mut right]).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "LiveShifterProcessor::process_in_place allocated {} times", allocations);
+}
+
+/// Test that `LiveShifterN::process_into` does not allocate
+#[test]
+fn test_fixed_channels_no_allocation() {
+    let shifter = LiveShifterBuilder::new(44100, 2).unwrap().build().unwrap();
+    let stereo = LiveShifterN::<2>::new(shifter).unwrap();
+    let block_size = stereo.block_size() as usize;
+
+    let input = [vec![0.1f32; block_size], vec![-0.1f32; block_size]];
+    let [mut out_ch1, mut out_ch2] = [vec![0.0f32; block_size], vec![0.0f32; block_size]];
+
+    let allocations = count_allocations(|| {
+        for _ in 0..100 {
+            stereo
+                .process_into(&[&input[0], &input[1]], &mut [&mut out_ch1, &mut out_ch2])
+                .unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "LiveShifterN::process_into allocated {} times", allocations);
+}
+
+/// Test that `process_into` does not allocate when converting and dithering integer samples
+#[test]
+fn test_process_into_i16_no_allocation() {
+    let shifter = LiveShifterBuilder::new(44100, 2)
+        .unwrap()
+        .dither(Dither::Triangular)
+        .build()
+        .unwrap();
+    let block_size = shifter.block_size() as usize;
+
+    let input = [vec![1000i16; block_size], vec![-1000i16; block_size]];
+    let mut output = [vec![0i16; block_size], vec![0i16; block_size]];
+    let input_slices: [&[i16]; 2] = [&input[0], &input[1]];
+
+    let allocations = count_allocations(|| {
+        for _ in 0..100 {
+            let [out_ch1, out_ch2] = &mut output;
+            shifter.process_into(&input_slices, &mut [out_ch1, out_ch2]).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "process_into allocated {} times", allocations);
+}
+
+/// Test that `LiveShifterStream::process_into` does not allocate with odd host buffer sizes
+#[test]
+fn test_stream_process_into_no_allocation() {
+    let shifter = LiveShifterBuilder::new(48000, 1).unwrap().build().unwrap();
+    let stream = LiveShifterStream::new(shifter);
+
+    let input = vec![0.5f32; 1024];
+    let mut output = vec![0.0f32; 1024];
+
+    let allocations = count_allocations(|| {
+        for (i, &size) in [64, 128, 100, 1, 333, 1024].iter().cycle().take(60).enumerate() {
+            stream.shifter().set_pitch_semitone((i % 12) as f64);
+            stream.process_into(&[&input[..size]], &mut [&mut output[..size]]).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "LiveShifterStream::process_into allocated {} times", allocations);
+}
+
+/// Test that `LiveShifterProcessor::process_into` does not allocate while applying controller changes
+#[test]
+fn test_processor_no_allocation() {
+    let (mut processor, controller) = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap().split();
+    let block_size = processor.block_size() as usize;
+
+    let input = vec![0.5f32; block_size];
+    let mut output = vec![0.0f32; block_size];
+
+    let allocations = count_allocations(|| {
+        for i in 0..100 {
+            controller.set_pitch_cent((i % 10) as f64 * 10.0);
+            controller.set_formant_scale(1.0 + (i % 5) as f64 * 0.1);
+            controller.set_formant_option(LiveShifterFormant::Preserved);
+            processor.process_into(&[&input], &mut [&mut output]).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "LiveShifterProcessor::process_into allocated {} times", allocations);
+}
+
+/// Test that a real-time `Stretcher` does not allocate in `process` and `retrieve_into`, including
+/// when the time ratio and pitch scale change, immediately or at scheduled frames
+#[test]
+fn test_stretcher_no_allocation() {
+    let stretcher = StretcherBuilder::new(44100, 2)
+        .unwrap()
+        .process_mode(StretcherProcessMode::RealTime)
+        .build()
+        .unwrap();
+    let block_size = 512;
+    stretcher.set_max_process_size(block_size).unwrap();
+
+    let input = [vec![0.5f32; block_size], vec![-0.5f32; block_size]];
+    let mut output = [vec![0.0f32; 4 * block_size], vec![0.0f32; 4 * block_size]];
+    let input_slices: [&[f32]; 2] = [&input[0], &input[1]];
+
+    let allocations = count_allocations(|| {
+        for i in 0..100 {
+            stretcher.set_time_ratio(1.0 + (i % 10) as f64 * 0.05);
+            stretcher.set_pitch_scale(1.0 + (i % 5) as f64 * 0.1);
+            stretcher.schedule_time_ratio(i * block_size as u64 + 100, 1.2).unwrap();
+            stretcher.process(&input_slices, false).unwrap();
+            let [out_ch1, out_ch2] = &mut output;
+            let mut output_slices: [&mut [f32]; 2] = [out_ch1, out_ch2];
+            stretcher.retrieve_into(&mut output_slices).unwrap();
+        }
+    });
+    assert_eq!(allocations, 0, "Stretcher::process and retrieve_into allocated {} times", allocations);
+}
@@ -1,6 +1,6 @@
 use rubberband::{LiveShifterBuilder, LiveShifterFormant, RubberBandError};
 use std::sync::{Arc, RwLock};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 use std::time::Duration;
 use rand::Rng;
@@ -21,7 +21,7 @@ fn test_method_concurrent_with_process<F>(method_call: F)
 where F: Fn(&Arc<rubberband::LiveShifter>, usize, usize) + Send + 'static,
 {
     let builder = LiveShifterBuilder::new(44100, 1).unwrap();
-    let shifter = Arc::new(builder.build());
+    let shifter = Arc::new(builder.build().unwrap());
     let mut handles = vec![];
 
     // Create a thread that keeps calling `process`
@@ -63,7 +63,7 @@ where F: Fn(&Arc<rubberband::LiveShifter>, usize, usize) + Send + 'static,
 #[test]
 fn test_concurrent_processing() {
     let builder = LiveShifterBuilder::new(44100, 1).unwrap();
-    let shifter = Arc::new(builder.build());
+    let shifter = Arc::new(builder.build().unwrap());
     let error_count = Arc::new(AtomicUsize::new(0));
 
     let mut handles = vec![];
@@ -74,7 +74,7 @@ fn test_concurrent_processing() {
         let error_count = error_count.clone();
         let handle = thread::spawn(move || {
             let block_size = 512;
-            let input = vec![vec![0.5f32; block_size]];
+            let input = [vec![0.5f32; block_size]];
             let input_slices: Vec<&[f32]> = input.iter().map(|v| v.as_slice()).collect();
 
             for _ in 0..100 {
@@ -145,7 +145,7 @@ fn test_set_formant_option() {
 #[test]
 fn test_reset() {
     let builder = LiveShifterBuilder::new(44100, 1).unwrap();
-    let shifter = Arc::new(builder.build());
+    let shifter = Arc::new(builder.build().unwrap());
     let mut handles = vec![];
 
     // Create a thread that keeps calling `process`
@@ -170,7 +170,7 @@ fn test_reset() {
     let shifter_ref = shifter.clone();
     let method_handle = thread::spawn(move || {
         for _ in 0..1000 {
-            shifter_ref.reset();
+            shifter_ref.reset().unwrap();
             thread::sleep(Duration::from_millis(1));
         }
     });
@@ -185,7 +185,7 @@ fn test_reset() {
 #[test]
 fn test_start_delay() {
     let builder = LiveShifterBuilder::new(48000, 1).unwrap();
-    let shifter = Arc::new(builder.build());
+    let shifter = Arc::new(builder.build().unwrap());
     let mut handles = vec![];
 
     // Compute the possible start delays for different pitch scales
@@ -229,6 +229,110 @@ fn test_start_delay() {
     // Check if all the delay values are among the expected delays
     let delays = delays.read().unwrap();
     for delay in delays.iter() {
-        assert!(expected_delays.contains(&delay), "Delay {} not in expected delays {:?}", delay, expected_delays);
+        assert!(expected_delays.contains(delay), "Delay {} not in expected delays {:?}", delay, expected_delays);
     }
-}
\ No newline at end of file
+}
+
+/// Test parameter changes from several controller threads while the processor runs
+#[test]
+fn test_split_controller() {
+    let shifter = LiveShifterBuilder::new(44100, 1).unwrap().build().unwrap();
+    let (mut processor, controller) = shifter.split();
+    let mut handles = vec![];
+
+    for t in 0..3 {
+        let controller = controller.clone();
+        let handle = thread::spawn(move || {
+            for i in 0..1000 {
+                let scale = 0.5 + 1.5 * i as f64 / 1000.0;
+                match t {
+                    0 => controller.set_pitch_scale(scale),
+                    1 => controller.set_formant_scale(scale),
+                    _ => controller.set_formant_option(if i % 2 == 0 {
+                        LiveShifterFormant::Shifted
+                    } else {
+                        LiveShifterFormant::Preserved
+                    }),
+                }
+                thread::sleep(Duration::from_micros(100));
+            }
+        });
+        handles.push(handle);
+    }
+
+    // Processing on its own thread never collides with the controllers
+    let process_handle = thread::spawn(move || {
+        let block_size = processor.block_size() as usize;
+        let input = vec![0.5f32; block_size];
+        let mut output = vec![0.0f32; block_size];
+        for _ in 0..200 {
+            assert!(processor.process_into(&[&input], &mut [&mut output]).is_ok());
+        }
+    });
+    handles.push(process_handle);
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+/// Test all runtime parameter setters hammering a shifter that is processing
+///
+/// Every setter must only publish its value for the processing thread to apply, never touch the
+/// C++ instance directly. Without sleeps, the calls overlap with `shift()` constantly, so a
+/// regression shows up as a data race when run under ThreadSanitizer, e.g.:
+///
+/// ```text
+/// RUSTFLAGS="-Zsanitizer=thread" cargo +nightly test -Zbuild-std \
+///     --target x86_64-unknown-linux-gnu --test thread_safety test_parameter_setters_race
+/// ```
+#[test]
+fn test_parameter_setters_race() {
+    let builder = LiveShifterBuilder::new(44100, 2).unwrap();
+    let shifter = Arc::new(builder.build().unwrap());
+    let running = Arc::new(AtomicBool::new(true));
+    let mut handles = vec![];
+
+    for t in 0..3 {
+        let shifter = shifter.clone();
+        let running = running.clone();
+        let handle = thread::spawn(move || {
+            let mut i = 0usize;
+            while running.load(Ordering::Relaxed) {
+                let scale = 0.5 + (i % 16) as f64 * 0.1;
+                match t {
+                    0 => shifter.set_pitch_scale(scale),
+                    1 => shifter.set_formant_scale(scale),
+                    _ => shifter.set_formant_option(if i % 2 == 1 {
+                        LiveShifterFormant::Preserved
+                    } else {
+                        LiveShifterFormant::Shifted
+                    }),
+                }
+                i += 1;
+            }
+        });
+        handles.push(handle);
+    }
+
+    let block_size = shifter.block_size() as usize;
+    let input = [vec![0.5f32; block_size], vec![-0.5f32; block_size]];
+    let mut output = [vec![0.0f32; block_size], vec![0.0f32; block_size]];
+    for _ in 0..200 {
+        let [in_ch1, in_ch2] = &input;
+        let [out_ch1, out_ch2] = &mut output;
+        assert!(shifter.process_into(&[in_ch1, in_ch2], &mut [out_ch1, out_ch2]).is_ok());
+    }
+
+    running.store(false, Ordering::Relaxed);
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // The last published values are the ones reported, and processing still works
+    shifter.set_formant_scale(1.5);
+    assert_eq!(shifter.formant_scale().target, 1.5);
+    let [in_ch1, in_ch2] = &input;
+    let [out_ch1, out_ch2] = &mut output;
+    assert!(shifter.process_into(&[in_ch1, in_ch2], &mut [out_ch1, out_ch2]).is_ok());
+}